@@ -1,6 +1,5 @@
 use egui::{FontDefinitions, FullOutput};
 use egui_demo_lib::DemoWindows;
-use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
 use egui_winit_platform::{Platform, PlatformDescriptor};
 use hydra::{base::{app::{App, EventHandler, Frame}, context::Context, pipeline::RenderPipelineBuilder, texture, vertex::{BasicInstanceData, ColoredVertex, TexturedVertex, VertexLayout}}, core::{camera::{self, PerspectiveParams}, mesh::Mesh, ui}};
 use image::GenericImageView;
@@ -311,7 +310,7 @@ fn update(state: &mut State,ctx: &Context){
     state.t+=0.001;
 
     //update camera with controller
-    state.camera_controller.update_camera(&mut state.camera);
+    state.camera_controller.update_camera_with_time(&mut state.camera,&ctx.time);
 
     //update cpu camera buffer
     state.cpu_matrix_uniform.matrix = state.camera.get_view_proj_matrix().into();
@@ -402,11 +401,7 @@ fn render(state: &State,ui: &mut ui::Ui, ctx: &Context,frame: Frame){
     //not sure what pixels per point is
     let paint_jobs = ui.platform.context().tessellate(output.shapes,ui.platform.context().pixels_per_point());
 
-    let screen_descriptor = ScreenDescriptor{
-        physical_width: ctx.config.width,
-        physical_height: ctx.config.height,
-        scale_factor: 1.0, //1 for rn, should actually query this, for high density displays
-    };
+    let screen_descriptor = ui.screen_descriptor(ctx);
     let tex_diff: egui::TexturesDelta = output.textures_delta;
 
     ui.ui_render_pass.add_textures(&ctx.device,&ctx.queue,&tex_diff).expect("added textures to ui pass");
@@ -471,7 +466,7 @@ fn mouse_input(state: &mut State,mouse_button: MouseButton,button_state: Element
 }
 
 
-fn main(){
+fn main() -> Result<(),hydra::Error>{
     App::new(init)
     .update(update)
     .render_with_ui(render)
@@ -480,5 +475,5 @@ fn main(){
     .on_mouse_move(mouse_move)
     .on_mouse_input(mouse_input)
     .with_title("example10_ui".to_string())
-    .run();
+    .run()
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use hydra::base::{app::{App, Frame}, context::Context};
+use winit::{event::{Event, WindowEvent}, event_loop::EventLoop, window::WindowBuilder};
+
+
+//Same as example1_window, but the event loop and the window belong to the host application
+//and hydra only gets the events forwarded to it
+struct State{
+    pub x: i32,
+}
+
+fn init(_app: &App<State>,_ctx: &Context) -> State{
+    println!("Creates state!");
+    State { x: 1 }
+}
+
+fn update(state: &mut State,_ctx: &Context){
+    state.x += 1;
+}
+
+fn render(state: &State,_ctx: &Context,frame: Frame){
+    println!("value {}",state.x);
+    frame.present();
+}
+
+
+fn main() -> Result<(),hydra::Error>{
+    let event_loop = EventLoop::new().unwrap();
+    let window = WindowBuilder::new().with_title("example1_embedded").build(&event_loop).unwrap();
+
+    let mut app = App::new(init)
+    .update(update)
+    .render(render);
+
+    app.attach(&window)?;
+
+    let mut frames = 0;
+    event_loop.run(move |event,target|{
+        //the host can look at the events too
+        if let Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } = &event{
+            frames += 1;
+        }
+
+        if let Err(e) = app.handle_event(event,target){
+            println!("hydra error: {e}");
+            target.exit();
+        }
+        if app.exit_requested(){
+            println!("exiting after {frames} frames");
+            target.exit();
+        }
+    }).unwrap();
+
+    Ok(())
+}
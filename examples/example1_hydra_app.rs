@@ -0,0 +1,42 @@
+use hydra::base::{app::{App, EventHandler, Frame, Key}, context::Context, hydra_app::HydraApp};
+use winit::{event::ElementState, keyboard::KeyCode::*};
+
+
+//Same as example1_window, but using the HydraApp trait instead of separate functions
+struct State{
+    pub x: i32,
+}
+
+impl HydraApp for State{
+    fn init(_app: &App<State>,_ctx: &Context) -> State{
+        println!("Creates state!");
+        State { x: 1 }
+    }
+
+    fn update(&mut self,_ctx: &Context){
+        self.x += 1;
+    }
+
+    fn render(&mut self,_ctx: &Context,frame: Frame){
+        println!("value {}",self.x);
+        frame.present();
+    }
+
+    fn on_key(&mut self,key: Key,_state: ElementState,control: &EventHandler){
+        println!("key: {:#?}",key);
+        if key == Escape{
+            control.exit();
+        }
+    }
+
+    fn on_exit(&mut self,_ctx: &Context){
+        println!("exiting at {}",self.x);
+    }
+}
+
+
+fn main() -> Result<(),hydra::Error>{
+    App::<State>::from_hydra_app()
+    .with_title("example1_hydra_app".to_string())
+    .run()
+}
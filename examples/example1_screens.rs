@@ -0,0 +1,117 @@
+use hydra::base::{app::{App, Frame}, context::Context, screen::{Screen, ScreenStack, Transition}};
+use winit::keyboard::KeyCode::*;
+
+
+//Menu -> editor -> preview, every screen clears the window to its own color
+//Enter opens the editor, P pushes the preview on top of it, Escape goes back (and quits from the menu)
+
+//data every screen can use
+struct Shared{
+    edits: u32,
+}
+
+fn clear(ctx: &Context,frame: Frame,color: wgpu::Color){
+    let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+        label: Some("my cmd encoder"),
+    });
+    {
+        let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor{
+            label: Some("my render pass"),
+            color_attachments: &[Some(ctx.color_attachment(&view,wgpu::LoadOp::Clear(color)))],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+    ctx.queue.submit(std::iter::once(encoder.finish()));
+    frame.present();
+}
+
+
+struct Menu;
+
+impl Screen<Shared> for Menu{
+    fn on_enter(&mut self,_shared: &mut Shared,_ctx: &Context){
+        println!("menu");
+    }
+
+    fn on_resume(&mut self,shared: &mut Shared,_ctx: &Context){
+        println!("back in the menu after {} edits",shared.edits);
+    }
+
+    fn render(&mut self,_shared: &mut Shared,ctx: &Context,frame: Frame){
+        clear(ctx,frame,wgpu::Color::RED);
+    }
+
+    //just pressed, so holding a key (key repeat) doesn't go through several screens at once
+    fn update(&mut self,_shared: &mut Shared,ctx: &Context) -> Transition<Shared>{
+        if ctx.input.is_key_just_pressed(Enter){
+            Transition::push(Editor)
+        }
+        else if ctx.input.is_key_just_pressed(Escape){
+            Transition::Quit
+        }
+        else{
+            Transition::None
+        }
+    }
+}
+
+
+struct Editor;
+
+impl Screen<Shared> for Editor{
+    fn on_enter(&mut self,_shared: &mut Shared,_ctx: &Context){
+        println!("editor");
+    }
+
+    fn update(&mut self,shared: &mut Shared,ctx: &Context) -> Transition<Shared>{
+        if ctx.input.is_key_just_pressed(Space){
+            shared.edits += 1;
+        }
+        if ctx.input.is_key_just_pressed(KeyP){
+            Transition::push(Preview)
+        }
+        else if ctx.input.is_key_just_pressed(Escape){
+            Transition::Pop
+        }
+        else{
+            Transition::None
+        }
+    }
+
+    fn render(&mut self,_shared: &mut Shared,ctx: &Context,frame: Frame){
+        clear(ctx,frame,wgpu::Color::GREEN);
+    }
+
+}
+
+
+struct Preview;
+
+impl Screen<Shared> for Preview{
+    fn on_enter(&mut self,shared: &mut Shared,_ctx: &Context){
+        println!("previewing {} edits",shared.edits);
+    }
+
+    fn render(&mut self,_shared: &mut Shared,ctx: &Context,frame: Frame){
+        clear(ctx,frame,wgpu::Color::BLUE);
+    }
+
+    fn update(&mut self,_shared: &mut Shared,ctx: &Context) -> Transition<Shared>{
+        if ctx.input.is_key_just_pressed(Escape){
+            Transition::Pop
+        }
+        else{
+            Transition::None
+        }
+    }
+}
+
+
+fn main() -> Result<(),hydra::Error>{
+    App::from_screens(|_ctx| ScreenStack::new(Shared { edits: 0 },Menu))
+    .with_title("example1_screens".to_string())
+    .run()
+}
@@ -70,12 +70,12 @@ fn cursor_move(state: &mut State,position: hydra::base::app::Position,size: Size
 
 
 
-fn main(){
+fn main() -> Result<(),hydra::Error>{
     App::new(init)
     .update(update)
     .render(render)
     .on_key(key_input)
     .on_cursor_move(cursor_move)
     .with_title("example2_renderpass".to_string())
-    .run();
+    .run()
 }
\ No newline at end of file
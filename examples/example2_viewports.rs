@@ -0,0 +1,76 @@
+use hydra::base::{app::{App, Frame, Key}, context::Context, viewport::Viewport, window::WindowConfig};
+
+
+//Main window plus a second window ("inspector") sharing the same device
+//Press I to close/reopen the inspector
+struct State{
+    t: f64,
+    inspector_open: bool,
+}
+
+fn init(_app: &App<State>,_ctx: &Context) -> State{
+    State { t: 0.0, inspector_open: true }
+}
+
+fn update(state: &mut State,ctx: &Context){
+    state.t = ctx.time.elapsed().as_secs_f64();
+    if ctx.input.is_key_just_pressed(Key::KeyI){
+        if state.inspector_open{
+            ctx.close_viewport("inspector");
+        }
+        else{
+            ctx.open_viewport("inspector");
+        }
+        state.inspector_open = !state.inspector_open;
+    }
+}
+
+//Clears a frame, works for both the main window and viewports
+fn clear(ctx: &Context,frame: Frame,color: wgpu::Color){
+    let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+        label: Some("clear encoder"),
+    });
+    {
+        let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor{
+            label: Some("clear pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment{
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations{
+                        load: wgpu::LoadOp::Clear(color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+    ctx.queue.submit(std::iter::once(encoder.finish()));
+    frame.present();
+}
+
+fn render(state: &State,ctx: &Context,frame: Frame){
+    clear(ctx,frame,wgpu::Color{ r: state.t.sin() * 0.5 + 0.5, g: 0.1, b: 0.1, a: 1.0 });
+}
+
+fn render_inspector(state: &mut State,ctx: &Context,_viewport: &Viewport,frame: Frame){
+    clear(ctx,frame,wgpu::Color{ r: 0.1, g: 0.1, b: state.t.cos() * 0.5 + 0.5, a: 1.0 });
+}
+
+
+fn main() -> Result<(),hydra::Error>{
+    App::new(init)
+    .update(update)
+    .render(render)
+    .with_viewport("inspector",WindowConfig::new().with_size(400,300),render_inspector)
+    .on_viewport_close(|state,_ctx,viewport|{
+        println!("closed {}",viewport.label);
+        state.inspector_open = false;
+    })
+    .with_title("example2_viewports".to_string())
+    .run()
+}
@@ -119,11 +119,11 @@ fn key_input(state: &mut State,key: hydra::base::app::Key,key_state: ElementStat
 
 
 
-fn main(){
+fn main() -> Result<(),hydra::Error>{
     App::new(init)
     .update(update)
     .render(render)
     .on_key(key_input)
     .with_title("example4_buffers".to_string())
-    .run();
+    .run()
 }
\ No newline at end of file
@@ -226,7 +226,7 @@ fn update(state: &mut State,ctx: &Context){
 
 
     //update camera with controller
-    state.camera_controller.update_camera(&mut state.camera);
+    state.camera_controller.update_camera_with_time(&mut state.camera,&ctx.time);
 
     //update cpu camera buffer
     state.cpu_matrix_uniform.matrix = state.camera.get_view_proj_matrix().into();
@@ -317,7 +317,7 @@ fn mouse_input(state: &mut State,mouse_button: MouseButton,button_state: Element
 
 
 
-fn main(){
+fn main() -> Result<(),hydra::Error>{
     App::new(init)
     .update(update)
     .render(render)
@@ -326,5 +326,5 @@ fn main(){
     .on_mouse_move(mouse_move)
     .on_mouse_input(mouse_input)
     .with_title("example6_uniforms".to_string())
-    .run();
+    .run()
 }
\ No newline at end of file
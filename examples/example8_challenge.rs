@@ -486,7 +486,7 @@ fn update(state: &mut State,ctx: &Context){
     state.main_pass.t+=0.001;
 
     //update camera with controller
-    state.main_pass.camera_controller.update_camera(&mut state.main_pass.camera);
+    state.main_pass.camera_controller.update_camera_with_time(&mut state.main_pass.camera,&ctx.time);
 
     //update cpu camera buffer
     state.main_pass.cpu_matrix_uniform.matrix = state.main_pass.camera.get_view_proj_matrix().into();
@@ -670,7 +670,7 @@ fn mouse_input(state: &mut State,mouse_button: MouseButton,button_state: Element
 }
 
 
-fn main(){
+fn main() -> Result<(),hydra::Error>{
     App::new(init)
     .update(update)
     .render(render)
@@ -679,7 +679,7 @@ fn main(){
     .on_mouse_move(mouse_move)
     .on_mouse_input(mouse_input)
     .with_title("example8_challenge".to_string())
-    .run();
+    .run()
 }
 
 /*
@@ -0,0 +1,72 @@
+use hydra::base::{app::{App, Frame}, context::Context, jobs::Jobs, texture::Texture, vertex::ColoredVertex};
+use hydra::core::mesh::Mesh;
+
+
+//Loads the bunny mesh and a texture on worker threads while the window keeps rendering
+//The screen is red while loading and turns green once everything arrived
+enum Loaded{
+    Mesh(hydra::Result<Mesh<ColoredVertex>>),
+    Image(hydra::Result<image::RgbaImage>),
+}
+
+struct State{
+    jobs: Jobs<Loaded>,
+    mesh: Option<Mesh<ColoredVertex>>,
+    texture: Option<Texture>,
+}
+
+fn init(app: &App<State,Loaded>,_ctx: &Context) -> State{
+    let jobs = app.jobs().expect("jobs need an event loop").clone();
+    jobs.load_mesh("assets/bunny.obj",Loaded::Mesh);
+    jobs.load_image("assets/happy_tree.png",Loaded::Image);
+
+    State { jobs, mesh: None, texture: None }
+}
+
+fn on_loaded(state: &mut State,ctx: &Context,event: Loaded){
+    match event{
+        Loaded::Mesh(Ok(mesh)) => {
+            println!("mesh loaded: {} indices",mesh.num_indices());
+            state.mesh = Some(mesh);
+        },
+        //gpu resources get created on the main thread
+        Loaded::Image(Ok(image)) => {
+            println!("image loaded: {:?}",image.dimensions());
+            state.texture = Some(Texture::from_image(ctx,&image));
+        },
+        Loaded::Mesh(Err(e)) | Loaded::Image(Err(e)) => println!("loading failed: {e}"),
+    }
+    println!("{} jobs left",state.jobs.pending());
+}
+
+fn render(state: &State,ctx: &Context,frame: Frame){
+    let color = match (&state.mesh,&state.texture){
+        (Some(_),Some(_)) => wgpu::Color::GREEN,
+        _ => wgpu::Color::RED,
+    };
+
+    let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+        label: Some("my cmd encoder"),
+    });
+    {
+        let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor{
+            label: Some("my render pass"),
+            color_attachments: &[Some(ctx.color_attachment(&view,wgpu::LoadOp::Clear(color)))],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+    ctx.queue.submit(std::iter::once(encoder.finish()));
+    frame.present();
+}
+
+
+fn main() -> Result<(),hydra::Error>{
+    App::with_user_events(init)
+    .render(render)
+    .on_user_event(|state,ctx,event,_control| on_loaded(state,ctx,event))
+    .with_title("example9_loading".to_string())
+    .run()
+}
@@ -1,8 +1,8 @@
-use hydra::{base::{app::{App, EventHandler, Frame}, context::Context, pipeline::RenderPipelineBuilder, texture, vertex::{BasicInstanceData, ColoredVertex, TexturedVertex, VertexLayout}}, core::{camera::{self, PerspectiveParams}, mesh::Mesh}};
+use hydra::{base::{app::{App, Frame}, context::Context, pipeline::RenderPipelineBuilder, plugin::{FpsPlugin, QuitPlugin}, texture, vertex::{BasicInstanceData, ColoredVertex, TexturedVertex, VertexLayout}, window::{FullscreenMode, WindowConfig}}, core::{camera::{self, FlyCameraPlugin, PerspectiveParams}, mesh::Mesh}};
 use image::GenericImageView;
 use nalgebra_glm::{identity, quat_cast, rotate_y, to_quat, translation, two_pi, vec3};
 use wgpu::{util::{BufferInitDescriptor, DeviceExt}, Backends, ImageCopyTexture, ImageCopyTextureBase, IndexFormat, ShaderModule, ShaderSource, VertexBufferLayout};
-use winit::{event::{ElementState, MouseButton}, keyboard::KeyCode::*, window};
+use winit::{keyboard::KeyCode::*, window};
 
 
 const NUM_INSTANCES: u32 = 10;
@@ -307,9 +307,12 @@ fn update(state: &mut State,ctx: &Context){
 
     state.t+=0.001;
 
-    //update camera with controller
-    state.camera_controller.update_camera(&mut state.camera);
+    //F11 toggles fullscreen
+    if ctx.input.is_key_just_pressed(F11){
+        ctx.toggle_fullscreen(FullscreenMode::Borderless);
+    }
 
+    //the camera was already moved by FlyCameraPlugin
     //update cpu camera buffer
     state.cpu_matrix_uniform.matrix = state.camera.get_view_proj_matrix().into();
 
@@ -418,31 +421,17 @@ fn resize(state: &mut State,ctx: &Context,width: u32,height: u32){
     state.depth_texture_view = state.depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
 }
 
-fn key_input(state: &mut State,key: hydra::base::app::Key,key_state: ElementState,event_handler: &EventHandler){
-    state.camera_controller.on_key_fn(key, key_state);
-    match key{
-        Escape => event_handler.exit(),
-        _ => {}
-    }
-}
-
-fn mouse_move(state: &mut State,delta: (f32,f32),event_handler: &EventHandler){
-    state.camera_controller.on_mouse_move_fn(delta);
-}
-
-fn mouse_input(state: &mut State,mouse_button: MouseButton,button_state: ElementState,event_handler: &EventHandler){
-    state.camera_controller.on_mouse_input_fn(button_state, mouse_button);
-}
 
 
-fn main(){
+fn main() -> Result<(),hydra::Error>{
     App::new(init)
     .update(update)
     .render(render)
     .on_window_resize(resize)
-    .on_key(key_input)
-    .on_mouse_move(mouse_move)
-    .on_mouse_input(mouse_input)
+    .add_plugin(FlyCameraPlugin::new(|state: &mut State| (&mut state.camera,&mut state.camera_controller)))
+    .add_plugin(QuitPlugin::new(Escape))
+    .add_plugin(FpsPlugin::default())
     .with_title("example9_mesh".to_string())
-    .run();
+    .with_window(WindowConfig::new().with_size(1280,720))
+    .run()
 }
\ No newline at end of file
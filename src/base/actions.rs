@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use winit::event::MouseButton;
+
+use crate::base::app::Key;
+use crate::base::input::Input;
+use crate::error::{Error, Result};
+
+//names of the actions/axes hydra itself uses
+pub const QUIT: &str = "quit";
+pub const SCREENSHOT: &str = "screenshot";
+pub const MOVE_FORWARD: &str = "move_forward";
+pub const MOVE_BACK: &str = "move_back";
+pub const MOVE_LEFT: &str = "move_left";
+pub const MOVE_RIGHT: &str = "move_right";
+pub const ROTATE: &str = "rotate";
+pub const LOOK_X: &str = "look_x";
+pub const LOOK_Y: &str = "look_y";
+
+//Something that triggers an action
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub enum Binding{
+    Key(Key),
+    Mouse(MouseButton),
+    //every binding has to be held (ex: ctrl+s)
+    Chord(Vec<Binding>),
+}
+
+impl Binding{
+    pub fn is_held(&self,input: &Input) -> bool{
+        match self{
+            Binding::Key(key) => input.is_key_held(*key),
+            Binding::Mouse(button) => input.is_button_held(*button),
+            Binding::Chord(bindings) => !bindings.is_empty() && bindings.iter().all(|binding| binding.is_held(input)),
+        }
+    }
+
+    //Chords count as just pressed when the last part of them gets pressed
+    pub fn is_just_pressed(&self,input: &Input) -> bool{
+        match self{
+            Binding::Key(key) => input.is_key_just_pressed(*key),
+            Binding::Mouse(button) => input.is_button_just_pressed(*button),
+            Binding::Chord(bindings) => self.is_held(input) && bindings.iter().any(|binding| binding.is_just_pressed(input)),
+        }
+    }
+
+    //Chords count as released when any part of them gets released
+    pub fn is_just_released(&self,input: &Input) -> bool{
+        match self{
+            Binding::Key(key) => input.is_key_just_released(*key),
+            Binding::Mouse(button) => input.is_button_just_released(*button),
+            Binding::Chord(bindings) => {
+                bindings.iter().any(|binding| binding.is_just_released(input)) &&
+                bindings.iter().all(|binding| binding.is_held(input) || binding.is_just_released(input))
+            },
+        }
+    }
+}
+
+//Something that drives an axis
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub enum AxisBinding{
+    //-1 while negative is held, 1 while positive is held
+    Keys{negative: Key,positive: Key},
+    //raw mouse motion this frame
+    MouseX,
+    MouseY,
+    //scroll wheel lines this frame
+    ScrollX,
+    ScrollY,
+}
+
+impl AxisBinding{
+    pub fn value(&self,input: &Input) -> f32{
+        match self{
+            AxisBinding::Keys { negative, positive } => {
+                input.is_key_held(*positive) as i32 as f32 - input.is_key_held(*negative) as i32 as f32
+            },
+            AxisBinding::MouseX => input.mouse_delta().0,
+            AxisBinding::MouseY => input.mouse_delta().1,
+            AxisBinding::ScrollX => input.scroll_delta().0,
+            AxisBinding::ScrollY => input.scroll_delta().1,
+        }
+    }
+}
+
+//Named actions and axes bound to inputs, so controls can be rebound without touching code
+//Saved/loaded as toml or ron depending on the file extension
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+#[serde(default)]
+pub struct ActionMap{
+    pub actions: BTreeMap<String,Vec<Binding>>,
+    pub axes: BTreeMap<String,Vec<AxisBinding>>,
+}
+
+//The default bindings used by hydra's camera controllers (WASD + left mouse drag)
+impl Default for ActionMap{
+    fn default() -> Self {
+        Self::new()
+            .bind(MOVE_FORWARD,Binding::Key(Key::KeyW))
+            .bind(MOVE_BACK,Binding::Key(Key::KeyS))
+            .bind(MOVE_LEFT,Binding::Key(Key::KeyA))
+            .bind(MOVE_RIGHT,Binding::Key(Key::KeyD))
+            .bind(ROTATE,Binding::Mouse(MouseButton::Left))
+            .bind_axis(LOOK_X,AxisBinding::MouseX)
+            .bind_axis(LOOK_Y,AxisBinding::MouseY)
+    }
+}
+
+impl ActionMap{
+    //No bindings at all
+    pub fn new() -> Self{
+        Self{
+            actions: BTreeMap::new(),
+            axes: BTreeMap::new(),
+        }
+    }
+
+    //Adds a binding to an action (an action can have several)
+    pub fn bind(mut self,action: &str,binding: Binding) -> Self{
+        self.add_binding(action,binding);
+        self
+    }
+
+    pub fn bind_axis(mut self,axis: &str,binding: AxisBinding) -> Self{
+        self.add_axis_binding(axis,binding);
+        self
+    }
+
+    pub fn add_binding(&mut self,action: &str,binding: Binding){
+        self.actions.entry(action.to_string()).or_default().push(binding);
+    }
+
+    pub fn add_axis_binding(&mut self,axis: &str,binding: AxisBinding){
+        self.axes.entry(axis.to_string()).or_default().push(binding);
+    }
+
+    //Replaces every binding of an action
+    pub fn rebind(&mut self,action: &str,bindings: Vec<Binding>){
+        self.actions.insert(action.to_string(),bindings);
+    }
+
+    pub fn rebind_axis(&mut self,axis: &str,bindings: Vec<AxisBinding>){
+        self.axes.insert(axis.to_string(),bindings);
+    }
+
+    pub fn bindings(&self,action: &str) -> &[Binding]{
+        self.actions.get(action).map(|bindings| bindings.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn axis_bindings(&self,axis: &str) -> &[AxisBinding]{
+        self.axes.get(axis).map(|bindings| bindings.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn is_bound(&self,action: &str) -> bool{
+        !self.bindings(action).is_empty()
+    }
+
+    //Adds the bindings of other, replacing actions/axes both maps bind
+    pub fn merge(&mut self,other: ActionMap){
+        self.actions.extend(other.actions);
+        self.axes.extend(other.axes);
+    }
+
+    //queries
+
+    pub fn is_held(&self,input: &Input,action: &str) -> bool{
+        self.bindings(action).iter().any(|binding| binding.is_held(input))
+    }
+
+    pub fn is_just_pressed(&self,input: &Input,action: &str) -> bool{
+        self.bindings(action).iter().any(|binding| binding.is_just_pressed(input))
+    }
+
+    pub fn is_just_released(&self,input: &Input,action: &str) -> bool{
+        self.bindings(action).iter().any(|binding| binding.is_just_released(input))
+    }
+
+    //Sum of every binding of the axis
+    pub fn axis(&self,input: &Input,axis: &str) -> f32{
+        self.axis_bindings(axis).iter().map(|binding| binding.value(input)).sum()
+    }
+
+    //files
+
+    pub fn from_toml(s: &str) -> Result<Self>{
+        toml::from_str(s).map_err(|e| Error::Actions(e.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String>{
+        toml::to_string_pretty(self).map_err(|e| Error::Actions(e.to_string()))
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self>{
+        ron::from_str(s).map_err(|e| Error::Actions(e.to_string()))
+    }
+
+    pub fn to_ron(&self) -> Result<String>{
+        ron::ser::to_string_pretty(self,ron::ser::PrettyConfig::default()).map_err(|e| Error::Actions(e.to_string()))
+    }
+
+    //Loads a .toml or .ron file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self>{
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(|e| Error::Actions(format!("can't read {}: {e}",path.display())))?;
+        match ConfigFormat::from_path(path)?{
+            ConfigFormat::Toml => Self::from_toml(&s),
+            ConfigFormat::Ron => Self::from_ron(&s),
+        }
+    }
+
+    //Saves as a .toml or .ron file
+    pub fn save(&self,path: impl AsRef<Path>) -> Result<()>{
+        let path = path.as_ref();
+        let s = match ConfigFormat::from_path(path)?{
+            ConfigFormat::Toml => self.to_toml()?,
+            ConfigFormat::Ron => self.to_ron()?,
+        };
+        std::fs::write(path,s).map_err(|e| Error::Actions(format!("can't write {}: {e}",path.display())))
+    }
+
+    //Loads the file if it exists, otherwise saves the defaults there so they can be customized
+    pub fn load_or_save(path: impl AsRef<Path>,defaults: ActionMap) -> Result<Self>{
+        let path = path.as_ref();
+        if path.exists(){
+            return Self::load(path);
+        }
+        defaults.save(path)?;
+        log::info!("Saved default input bindings to {}",path.display());
+        Ok(defaults)
+    }
+}
+
+enum ConfigFormat{
+    Toml,
+    Ron,
+}
+
+impl ConfigFormat{
+    fn from_path(path: &Path) -> Result<Self>{
+        match path.extension().and_then(|extension| extension.to_str()){
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("ron") => Ok(ConfigFormat::Ron),
+            _ => Err(Error::Actions(format!("{} isn't a .toml or .ron file",path.display())))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use winit::event::ElementState::{Pressed, Released};
+
+    use super::*;
+
+    fn save_chord() -> Binding{
+        Binding::Chord(vec![Binding::Key(Key::ControlLeft),Binding::Key(Key::KeyS)])
+    }
+
+    #[test]
+    fn chord_press_and_release(){
+        let chord = save_chord();
+        let mut input = Input::new();
+
+        //only part of the chord
+        input.press_key(Key::ControlLeft,Pressed);
+        assert!(!chord.is_held(&input) && !chord.is_just_pressed(&input));
+        input.end_frame();
+
+        //completing it counts as a press
+        input.press_key(Key::KeyS,Pressed);
+        assert!(chord.is_held(&input) && chord.is_just_pressed(&input));
+        input.end_frame();
+        assert!(chord.is_held(&input) && !chord.is_just_pressed(&input));
+
+        //releasing any part releases it, once
+        input.press_key(Key::KeyS,Released);
+        assert!(!chord.is_held(&input) && chord.is_just_released(&input));
+        input.end_frame();
+        input.press_key(Key::ControlLeft,Released);
+        assert!(!chord.is_just_released(&input));
+    }
+
+    #[test]
+    fn chord_order_doesnt_matter(){
+        let chord = save_chord();
+        let mut input = Input::new();
+        input.press_key(Key::KeyS,Pressed);
+        input.end_frame();
+        input.press_key(Key::ControlLeft,Pressed);
+        assert!(chord.is_just_pressed(&input));
+
+        //empty chords never trigger
+        assert!(!Binding::Chord(vec![]).is_held(&input));
+    }
+
+    #[test]
+    fn actions_and_axes(){
+        let actions = ActionMap::new()
+            .bind("save",save_chord())
+            .bind("save",Binding::Key(Key::F2))
+            .bind_axis("zoom",AxisBinding::Keys { negative: Key::KeyQ, positive: Key::KeyE });
+        let mut input = Input::new();
+        input.press_key(Key::F2,Pressed);
+        input.press_key(Key::KeyQ,Pressed);
+        assert!(actions.is_just_pressed(&input,"save"));
+        assert_eq!(actions.axis(&input,"zoom"),-1.0);
+        assert!(!actions.is_held(&input,"unbound"));
+    }
+
+    fn custom_map() -> ActionMap{
+        let mut actions = ActionMap::default()
+            .bind(QUIT,Binding::Key(Key::Escape))
+            .bind("save",save_chord())
+            .bind("fire",Binding::Mouse(MouseButton::Right))
+            .bind_axis("zoom",AxisBinding::ScrollY);
+        actions.rebind(MOVE_FORWARD,vec![Binding::Key(Key::ArrowUp)]);
+        actions
+    }
+
+    #[test]
+    fn toml_and_ron_round_trip(){
+        let actions = custom_map();
+        assert_eq!(ActionMap::from_toml(&actions.to_toml().unwrap()).unwrap(),actions);
+        assert_eq!(ActionMap::from_ron(&actions.to_ron().unwrap()).unwrap(),actions);
+    }
+
+    #[test]
+    fn save_and_load(){
+        let actions = custom_map();
+        let dir = std::env::temp_dir();
+        for extension in ["toml","ron"]{
+            let path = dir.join(format!("hydra_actions_test_{}.{extension}",std::process::id()));
+            actions.save(&path).unwrap();
+            let loaded = ActionMap::load(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.unwrap(),actions);
+        }
+        assert!(actions.save(dir.join("hydra_actions_test.json")).is_err());
+    }
+
+    #[test]
+    fn load_or_save_writes_defaults(){
+        let path = std::env::temp_dir().join(format!("hydra_actions_defaults_{}.toml",std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let defaults = custom_map();
+        assert_eq!(ActionMap::load_or_save(&path,defaults.clone()).unwrap(),defaults);
+        //the second time it comes from the file
+        assert_eq!(ActionMap::load_or_save(&path,ActionMap::new()).unwrap(),defaults);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+//Environment variable that overrides the adapter picked by a ContextBuilder (same syntax as AdapterSelector::parse)
+pub const ADAPTER_ENV_VAR: &str = "HYDRA_ADAPTER";
+
+//Ways to explicitly pick an adapter instead of letting wgpu choose based on power preference
+#[derive(Debug,Clone,PartialEq)]
+pub enum AdapterSelector{
+    //position in the list returned by enumerate_adapters
+    Index(usize),
+    //case insensitive substring of the adapter name (ex: "llvmpipe", "nvidia")
+    Name(String),
+    DeviceType(wgpu::DeviceType),
+    Backend(wgpu::Backend),
+}
+
+impl AdapterSelector{
+
+    //Parses an index ("1"), device type ("discrete", "integrated", "virtual", "cpu"),
+    //backend ("vulkan", "metal", "dx12", "gl", "webgpu") or else treats the string as a name substring
+    pub fn parse(s: &str) -> Self{
+        let s = s.trim();
+        if let Ok(index) = s.parse::<usize>(){
+            return AdapterSelector::Index(index);
+        }
+        match s.to_lowercase().as_str(){
+            "discrete" | "discretegpu" => AdapterSelector::DeviceType(wgpu::DeviceType::DiscreteGpu),
+            "integrated" | "integratedgpu" => AdapterSelector::DeviceType(wgpu::DeviceType::IntegratedGpu),
+            "virtual" | "virtualgpu" => AdapterSelector::DeviceType(wgpu::DeviceType::VirtualGpu),
+            "cpu" | "software" => AdapterSelector::DeviceType(wgpu::DeviceType::Cpu),
+            "vulkan" => AdapterSelector::Backend(wgpu::Backend::Vulkan),
+            "metal" => AdapterSelector::Backend(wgpu::Backend::Metal),
+            "dx12" => AdapterSelector::Backend(wgpu::Backend::Dx12),
+            "gl" | "opengl" => AdapterSelector::Backend(wgpu::Backend::Gl),
+            "webgpu" => AdapterSelector::Backend(wgpu::Backend::BrowserWebGpu),
+            _ => AdapterSelector::Name(s.to_string())
+        }
+    }
+
+    //Reads the selector from HYDRA_ADAPTER if it's set
+    pub fn from_env() -> Option<Self>{
+        std::env::var(ADAPTER_ENV_VAR).ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| Self::parse(&s))
+    }
+
+    pub fn matches(&self,index: usize,info: &wgpu::AdapterInfo) -> bool{
+        match self{
+            AdapterSelector::Index(i) => *i == index,
+            AdapterSelector::Name(name) => info.name.to_lowercase().contains(&name.to_lowercase()),
+            AdapterSelector::DeviceType(device_type) => info.device_type == *device_type,
+            AdapterSelector::Backend(backend) => info.backend == *backend,
+        }
+    }
+
+    //Picks the first matching adapter out of the ones available for the given backends
+    pub fn select(&self,instance: &wgpu::Instance,backends: wgpu::Backends,compatible_surface: Option<&wgpu::Surface<'_>>) -> Option<wgpu::Adapter>{
+        instance.enumerate_adapters(backends)
+            .into_iter()
+            .enumerate()
+            .filter(|(i,adapter)| self.matches(*i,&adapter.get_info()))
+            .map(|(_,adapter)| adapter)
+            .find(|adapter| match compatible_surface{
+                Some(surface) => adapter.is_surface_supported(surface),
+                None => true
+            })
+    }
+}
+
+//Lists every adapter available for the given backends, the index in this list is what AdapterSelector::Index refers to
+pub fn enumerate_adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo>{
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor{
+        backends,
+        ..Default::default()
+    });
+    enumerate_instance_adapters(&instance,backends)
+}
+
+pub(crate) fn enumerate_instance_adapters(instance: &wgpu::Instance,backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo>{
+    instance.enumerate_adapters(backends)
+        .iter()
+        .map(|adapter| adapter.get_info())
+        .collect()
+}
@@ -1,63 +1,194 @@
-use colored::Colorize;
-use wgpu::SurfaceTexture;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
 use winit::dpi::{PhysicalPosition, PhysicalSize};
-use winit::event::{DeviceEvent,ElementState, Event, KeyEvent, MouseButton};
-use winit::event_loop::EventLoopWindowTarget;
-use winit::keyboard::PhysicalKey::Code;
-use winit::window::WindowBuilder;
-use crate::base::context::Context;
+use winit::event::{DeviceEvent,ElementState, Event, MouseButton, WindowEvent};
+use winit::event_loop::{EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget};
+use crate::base::actions;
+use crate::base::context::{Context, ContextBuilder};
+use crate::base::capture::{RecordSettings, Recorder};
+use crate::base::frame::FrameTexture;
+use crate::base::texture::Texture;
+use crate::base::input::InputEvent;
+use crate::base::jobs::Jobs;
+use crate::base::plugin::{Plugin, ScreenshotPlugin};
+use crate::base::replay::{InputRecorder, InputRecording, InputReplay, RecordedEvent};
+use crate::base::viewport::{Viewport, ViewportRequest};
+use crate::base::window::WindowConfig;
 use crate::core::ui::{self, Ui};
+use crate::error::{Error, Result};
 
 pub type Key = winit::keyboard::KeyCode;
-pub type Frame = SurfaceTexture;
+pub use crate::base::frame::Frame;
+pub use crate::base::actions::{ActionMap, AxisBinding, Binding};
 pub type Position = PhysicalPosition<f64>;
 pub type Size = PhysicalSize<u32>;
-pub type EventHandler = EventLoopWindowTarget<()>;
+//E is the type of the app's user events (see on_user_event)
+pub type EventHandler<E = ()> = EventLoopWindowTarget<E>;
+
+//target format and time step of headless runs
+const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const HEADLESS_FRAME_DELTA: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+
+//Boxed callbacks, so closures capturing their environment can be used as well as plain functions
+pub type InitFn<T,E = ()> = Box<dyn FnMut(&App<'_,T,E>,&Context) -> T>;
+pub type UpdateFn<T> = Box<dyn FnMut(&mut T,&Context)>;
+pub type RenderFn<T> = Box<dyn FnMut(&mut T,&Context,Frame)>;
+pub type RenderWithUiFn<T> = Box<dyn FnMut(&mut T,&mut ui::Ui,&Context,Frame)>;
+pub type ResizeFn<T> = Box<dyn FnMut(&mut T,&Context,u32,u32)>;
+pub type ContextFn<T> = Box<dyn FnMut(&mut T,&Context)>;
+pub type ControlFn<T,E = ()> = Box<dyn FnMut(&mut T,&Context,&EventHandler<E>)>;
+pub type KeyFn<T,E = ()> = Box<dyn FnMut(&mut T,Key,ElementState,&EventHandler<E>)>;
+pub type CursorMoveFn<T,E = ()> = Box<dyn FnMut(&mut T,Position,Size,&EventHandler<E>)>;
+pub type MouseMoveFn<T,E = ()> = Box<dyn FnMut(&mut T,(f32,f32),&EventHandler<E>)>;
+pub type MouseInputFn<T,E = ()> = Box<dyn FnMut(&mut T,MouseButton,ElementState,&EventHandler<E>)>;
+pub type ScrollFn<T,E = ()> = Box<dyn FnMut(&mut T,(f32,f32),&EventHandler<E>)>;
+pub type TextFn<T,E = ()> = Box<dyn FnMut(&mut T,&str,&EventHandler<E>)>;
+pub type FileDropFn<T,E = ()> = Box<dyn FnMut(&mut T,PathBuf,&EventHandler<E>)>;
+pub type FlagFn<T,E = ()> = Box<dyn FnMut(&mut T,bool,&EventHandler<E>)>;
+pub type FrameFn<T> = Box<dyn FnMut(&mut T,&Context,&wgpu::Texture)>;
+pub type ScaleFactorFn<T> = Box<dyn FnMut(&mut T,&Context,f64)>;
+pub type ViewportRenderFn<T> = Box<dyn FnMut(&mut T,&Context,&Viewport,Frame)>;
+pub type ViewportFn<T> = Box<dyn FnMut(&mut T,&Context,&Viewport)>;
+pub type UserEventFn<T,E> = Box<dyn FnMut(&mut T,&Context,E,&EventHandler<E>)>;
+
+
+//Extra window added with with_viewport, open while viewport is some
+struct ViewportEntry<T>{
+    label: String,
+    config: WindowConfig,
+    render_fn: ViewportRenderFn<T>,
+    viewport: Option<Viewport>,
+    //opened when the app starts
+    open_on_start: bool,
+}
 
 
 //App state
-pub struct App<'a,T>{
+pub struct App<'a,T,E: 'static = ()>{
     context: Option<crate::base::context::Context<'a>>,
     window: Option<&'a winit::window::Window>,
     ui: Option<Ui>,
     state: Option<T>,
-    init_fn: fn(&App<T>,ctx: &Context) -> T,
-    update_fn: Option<fn(state: &mut T,ctx: &Context)>,
-    render_fn: Option<fn(state: &T,ctx: &Context,frame: Frame)>,
-    render_with_ui_fn: Option<fn(state: &T,ui: &mut ui::Ui, ctx: &Context,frame: Frame)>,
-    on_window_resize: Option<fn(state: &mut T,ctx: &Context,width: u32,height: u32)>,
+    //only none while it's being called
+    init_fn: Option<InitFn<T,E>>,
+    update_fn: Option<UpdateFn<T>>,
+    fixed_update_fn: Option<UpdateFn<T>>,
+    fixed_timestep: Option<Duration>,
+    render_fn: Option<RenderFn<T>>,
+    render_with_ui_fn: Option<RenderWithUiFn<T>>,
+    on_window_resize: Option<ResizeFn<T>>,
+    on_out_of_memory_fn: Option<ControlFn<T,E>>,
+    on_device_recreated_fn: Option<ContextFn<T>>,
+    on_exit_fn: Option<ContextFn<T>>,
+    on_frame_fn: Option<FrameFn<T>>,
 
 
     //input functions
-    on_key_fn: Option<fn(state: &mut T,key: Key,key_state: ElementState,control: &EventLoopWindowTarget<()>)>,
-    on_cursor_move_fn: Option<fn(state: &mut T,p: Position,size: Size,control: &EventLoopWindowTarget<()>)>,
-    on_mouse_move_fn: Option<fn(state: &mut T,delta: (f32,f32),control: &EventLoopWindowTarget<()>)>,
-    on_mouse_input_fn: Option<fn(state: &mut T,mouse_button: MouseButton,button_state: ElementState,control: &EventLoopWindowTarget<()>)>,
+    on_key_fn: Option<KeyFn<T,E>>,
+    on_cursor_move_fn: Option<CursorMoveFn<T,E>>,
+    on_mouse_move_fn: Option<MouseMoveFn<T,E>>,
+    on_mouse_input_fn: Option<MouseInputFn<T,E>>,
+    on_scroll_fn: Option<ScrollFn<T,E>>,
+    on_text_fn: Option<TextFn<T,E>>,
+
+
+    //window functions
+    on_file_drop_fn: Option<FileDropFn<T,E>>,
+    on_focus_fn: Option<FlagFn<T,E>>,
+    on_occluded_fn: Option<FlagFn<T,E>>,
+    on_scale_factor_changed_fn: Option<ScaleFactorFn<T>>,
+    on_viewport_resize_fn: Option<ViewportFn<T>>,
+    on_viewport_close_fn: Option<ViewportFn<T>>,
+    on_user_event_fn: Option<UserEventFn<T,E>>,
+    //nothing gets rendered while the window can't be seen
+    occluded: bool,
+    //frames were skipped because the window was hidden, the frame clock restarts once it's visible again
+    hidden: bool,
+    ime: bool,
 
 
     //misc customization
     title: String,
-    
+    window_config: WindowConfig,
+    context_builder: ContextBuilder,
+    //named input bindings, optionally loaded from/saved to a file before running
+    actions: ActionMap,
+    actions_file: Option<PathBuf>,
+    //input recording/replay
+    input_record_path: Option<PathBuf>,
+    input_recorder: Option<InputRecorder>,
+    input_replay_path: Option<PathBuf>,
+    input_replay: Option<InputReplay>,
+    //records frames to disk, exits when done
+    record_settings: Option<RecordSettings>,
+    recorder: Option<Recorder>,
+    //extra windows sharing the context's device
+    viewports: Vec<ViewportEntry<T>>,
+    //worker threads sending their results as user events, none until there's an event loop
+    jobs: Option<Jobs<E>>,
+    plugins: Vec<Box<dyn Plugin<T,E>>>,
 }
 
 impl<'window,T> App<'window,T>{
 
-    pub fn new(init: fn(&App<T>,ctx: &Context) -> T) -> App<'window,T> {
-        App::<T>{
+    pub fn new(init: impl FnMut(&App<T>,&Context) -> T + 'static) -> App<'window,T> {
+        App::with_user_events(init)
+    }
+}
+
+impl<'window,T,E: Send + 'static> App<'window,T,E>{
+
+    //Same as new, for apps whose event loop carries user events of type E (see on_user_event and jobs)
+    pub fn with_user_events(init: impl FnMut(&App<T,E>,&Context) -> T + 'static) -> App<'window,T,E> {
+        App::<T,E>{
             context: None,
             window: None,
             ui: None,
             state:None,
-            init_fn: init,
+            init_fn: Some(Box::new(init)),
             update_fn: None,
+            fixed_update_fn: None,
+            fixed_timestep: None,
             render_fn: None,
             render_with_ui_fn: None,
             on_window_resize: None,
+            on_out_of_memory_fn: None,
+            on_device_recreated_fn: None,
+            on_exit_fn: None,
+            on_frame_fn: None,
             on_key_fn: None,
             on_cursor_move_fn: None,
             on_mouse_move_fn: None,
             on_mouse_input_fn: None,
-            title: "hydra app".to_string()
+            on_scroll_fn: None,
+            on_text_fn: None,
+            on_file_drop_fn: None,
+            on_focus_fn: None,
+            on_occluded_fn: None,
+            on_scale_factor_changed_fn: None,
+            on_viewport_resize_fn: None,
+            on_viewport_close_fn: None,
+            on_user_event_fn: None,
+            occluded: false,
+            hidden: false,
+            ime: false,
+            title: "hydra app".to_string(),
+            window_config: WindowConfig::default(),
+            context_builder: ContextBuilder::default(),
+            actions: ActionMap::default(),
+            actions_file: None,
+            input_record_path: None,
+            input_recorder: None,
+            input_replay_path: None,
+            input_replay: None,
+            record_settings: None,
+            recorder: None,
+            viewports: vec![],
+            jobs: None,
+            plugins: vec![],
         }
     }
 
@@ -69,209 +200,1021 @@ impl<'window,T> App<'window,T>{
         self.window
     }
 
+    pub fn ui(&self) -> Option<&Ui>{
+        self.ui.as_ref()
+    }
+
+    pub fn state(&self) -> Option<&T>{
+        self.state.as_ref()
+    }
+
+    pub fn state_mut(&mut self) -> Option<&mut T>{
+        self.state.as_mut()
+    }
+
+    //The viewport with this label, if its window is open
+    pub fn viewport(&self,label: &str) -> Option<&Viewport>{
+        self.viewports.iter().find(|entry| entry.label == label).and_then(|entry| entry.viewport.as_ref())
+    }
+
+    //Creates the window of a viewport added with with_viewport (does nothing if it's already open)
+    //Callbacks can use ctx.open_viewport instead
+    pub fn open_viewport(&mut self,target: &EventHandler<E>,label: &str) -> Result<()>{
+        let ctx = match self.context.as_ref(){
+            Some(ctx) => ctx,
+            None => return Ok(())
+        };
+        let entry = match self.viewports.iter_mut().find(|entry| entry.label == label){
+            Some(entry) => entry,
+            None => {
+                log::warn!("No viewport called {label}");
+                return Ok(());
+            }
+        };
+        if entry.viewport.is_some(){
+            return Ok(());
+        }
+
+        let window = entry.config.builder(&entry.label)?.build(target)?;
+        entry.config.apply(&window)?;
+        let viewport = Viewport::new(ctx,&entry.label,Arc::new(window))?;
+        viewport.window.request_redraw();
+        entry.viewport = Some(viewport);
+        Ok(())
+    }
+
+    //Closes the window of a viewport, calling on_viewport_close
+    pub fn close_viewport(&mut self,label: &str){
+        let entry = match self.viewports.iter_mut().find(|entry| entry.label == label){
+            Some(entry) => entry,
+            None => return
+        };
+        if let Some(viewport) = entry.viewport.take(){
+            if let (Some(f),Some(state),Some(ctx)) = (self.on_viewport_close_fn.as_mut(),self.state.as_mut(),self.context.as_ref()){
+                f(state,ctx,&viewport);
+            }
+        }
+    }
+
+    //True once the app wants to stop (window closed, quit action, ctx.request_exit, recording/replay done...)
+    //run() exits by itself, a host application embedding the app should check this
+    pub fn exit_requested(&self) -> bool{
+        self.context.as_ref().is_some_and(|ctx| ctx.is_exit_requested())
+    }
+
+    //For embedding the app in a host application that owns the event loop and the window:
+    //attach to the window, then forward the loop's events with handle_event
+    //Creates the context and the app state (blocks until the gpu device is ready)
+    pub fn attach(&mut self,window: &'window winit::window::Window) -> Result<()>{
+        pollster::block_on(self.attach_async(window))?;
+        self.start();
+        window.request_redraw();
+        Ok(())
+    }
+
+    //Handles an event of the host's loop, events of other windows are ignored
+    //Redraw requests of the attached window run a frame (see render_frame), LoopExiting calls on_exit and saves recordings
+    //(for jobs and user events, pass the host loop's proxy to with_event_proxy)
+    pub fn handle_event(&mut self,event: Event<E>,control_flow: &EventHandler<E>) -> Result<()>{
+        if self.context.is_none(){
+            return Ok(());
+        }
+        self.process_event(event,control_flow)
+    }
+
+    //Worker threads (started by the first job) whose results arrive in on_user_event, none while running headless
+    //Clone it in init to start jobs later on
+    pub fn jobs(&self) -> Option<&Jobs<E>>{
+        self.jobs.as_ref()
+    }
 
-    pub fn update(mut self,f: fn(state: &mut T,ctx: &Context)) -> Self{
-        self.update_fn = Some(f);
+    //For sending user events from threads hydra doesn't manage
+    pub fn proxy(&self) -> Option<EventLoopProxy<E>>{
+        self.jobs.as_ref().map(|jobs| jobs.proxy().clone())
+    }
+
+
+    pub fn update(mut self,f: impl FnMut(&mut T,&Context) + 'static) -> Self{
+        self.update_fn = Some(Box::new(f));
+        self
+    }
+
+    //Called at a fixed rate (every timestep of scaled time) before update, possibly several times per frame
+    //ctx.time.alpha() tells render how far it is between the last two fixed updates
+    pub fn fixed_update(mut self,timestep: Duration,f: impl FnMut(&mut T,&Context) + 'static) -> Self{
+        self.fixed_update_fn = Some(Box::new(f));
+        self.fixed_timestep = Some(timestep);
         self
     }
 
-    pub fn render(mut self,f: fn(state: &T,&Context,frame: Frame)) -> Self{
-        self.render_fn = Some(f);
-        if let Some(_) = self.render_with_ui_fn{
+    pub fn render(self,mut f: impl FnMut(&T,&Context,Frame) + 'static) -> Self{
+        self.render_mut(move |state,ctx,frame| f(state,ctx,frame))
+    }
+
+    pub fn render_with_ui(self,mut f: impl FnMut(&T,&mut ui::Ui,&Context,Frame) + 'static) -> Self{
+        self.render_with_ui_mut(move |state,ui,ctx,frame| f(state,ui,ctx,frame))
+    }
+
+    //Same as render, but the state can be changed while rendering
+    pub fn render_mut(mut self,f: impl FnMut(&mut T,&Context,Frame) + 'static) -> Self{
+        self.render_fn = Some(Box::new(f));
+        if self.render_with_ui_fn.is_some(){
             panic!("Can't have both a render and render_with_ui function!");
         }
         self
     }
 
-    pub fn render_with_ui(mut self,f: fn(state: &T,ui: &mut ui::Ui,&Context,frame: Frame)) -> Self{
-        self.render_with_ui_fn = Some(f);
-        if let Some(_) = self.render_fn{
+    //Same as render_with_ui, but the state can be changed while rendering
+    pub fn render_with_ui_mut(mut self,f: impl FnMut(&mut T,&mut ui::Ui,&Context,Frame) + 'static) -> Self{
+        self.render_with_ui_fn = Some(Box::new(f));
+        if self.render_fn.is_some(){
             panic!("Can't have both a render and render_with_ui function!");
         }
+        self
+    }
+
+    pub fn on_window_resize(mut self,f: impl FnMut(&mut T,&Context,u32,u32) + 'static) -> Self{
+        self.on_window_resize = Some(Box::new(f));
+        self
+    }
+
+
+    //Called if the surface runs out of memory when acquiring a frame (otherwise run() exits with the error)
+    pub fn on_out_of_memory(mut self,f: impl FnMut(&mut T,&Context,&EventHandler<E>) + 'static) -> Self{
+        self.on_out_of_memory_fn = Some(Box::new(f));
+        self
+    }
 
+    //Called after a lost device was recreated, gpu resources (pipelines,buffers,textures...) need to be rebuilt here
+    pub fn on_device_recreated(mut self,f: impl FnMut(&mut T,&Context) + 'static) -> Self{
+        self.on_device_recreated_fn = Some(Box::new(f));
         self
     }
 
-    pub fn on_window_resize(mut self,f: fn(state: &mut T,ctx: &Context,width: u32,height: u32)) -> Self{
-        self.on_window_resize = Some(f);
+    //Called once when the event loop exits
+    pub fn on_exit(mut self,f: impl FnMut(&mut T,&Context) + 'static) -> Self{
+        self.on_exit_fn = Some(Box::new(f));
         self
     }
 
+    //Called with every presented frame before it goes to the screen (ex: to check or save it with capture::capture_texture)
+    pub fn on_frame(mut self,f: impl FnMut(&mut T,&Context,&wgpu::Texture) + 'static) -> Self{
+        self.on_frame_fn = Some(Box::new(f));
+        self
+    }
 
-    pub fn on_key(mut self,f: fn(state: &mut T,key: Key,key_state: ElementState,control: &EventLoopWindowTarget<()>)) -> Self{
-        self.on_key_fn = Some(f);
+    pub fn on_key(mut self,f: impl FnMut(&mut T,Key,ElementState,&EventHandler<E>) + 'static) -> Self{
+        self.on_key_fn = Some(Box::new(f));
         self
     }
 
-    pub fn on_cursor_move(mut self,f: fn(state: &mut T,p: Position,size: Size,control: &EventLoopWindowTarget<()>)) -> Self{
-        self.on_cursor_move_fn = Some(f);
+    pub fn on_cursor_move(mut self,f: impl FnMut(&mut T,Position,Size,&EventHandler<E>) + 'static) -> Self{
+        self.on_cursor_move_fn = Some(Box::new(f));
         self
     }
 
-    pub fn on_mouse_move(mut self,f: fn(state: &mut T,delta: (f32,f32),control: &EventLoopWindowTarget<()>)) -> Self{
-        self.on_mouse_move_fn = Some(f);
+    pub fn on_mouse_move(mut self,f: impl FnMut(&mut T,(f32,f32),&EventHandler<E>) + 'static) -> Self{
+        self.on_mouse_move_fn = Some(Box::new(f));
         self
     }
 
-    pub fn on_mouse_input(mut self,f: fn(state: &mut T,mouse_button: MouseButton,button_state: ElementState,control: &EventLoopWindowTarget<()>)) -> Self{
-        self.on_mouse_input_fn = Some(f);
+    pub fn on_mouse_input(mut self,f: impl FnMut(&mut T,MouseButton,ElementState,&EventHandler<E>) + 'static) -> Self{
+        self.on_mouse_input_fn = Some(Box::new(f));
         self
     }
     
 
+    //Scroll wheel movement in lines (touchpad pixel deltas get converted)
+    pub fn on_scroll(mut self,f: impl FnMut(&mut T,(f32,f32),&EventHandler<E>) + 'static) -> Self{
+        self.on_scroll_fn = Some(Box::new(f));
+        self
+    }
+
+    //Text typed into the window, for text fields and such (ime input too if enabled with with_ime)
+    pub fn on_text(mut self,f: impl FnMut(&mut T,&str,&EventHandler<E>) + 'static) -> Self{
+        self.on_text_fn = Some(Box::new(f));
+        self
+    }
+
+    //Called for every file dropped onto the window
+    pub fn on_file_drop(mut self,f: impl FnMut(&mut T,PathBuf,&EventHandler<E>) + 'static) -> Self{
+        self.on_file_drop_fn = Some(Box::new(f));
+        self
+    }
+
+    //Called with true when the window gains focus, false when it loses it
+    pub fn on_focus(mut self,f: impl FnMut(&mut T,bool,&EventHandler<E>) + 'static) -> Self{
+        self.on_focus_fn = Some(Box::new(f));
+        self
+    }
+
+    //Called with true when the window gets hidden (minimized, fully covered...), false when it's visible again
+    //Rendering stops while the window is hidden
+    pub fn on_occluded(mut self,f: impl FnMut(&mut T,bool,&EventHandler<E>) + 'static) -> Self{
+        self.on_occluded_fn = Some(Box::new(f));
+        self
+    }
+
+    //Called when the window moves to a monitor with a different scale factor
+    pub fn on_scale_factor_changed(mut self,f: impl FnMut(&mut T,&Context,f64) + 'static) -> Self{
+        self.on_scale_factor_changed_fn = Some(Box::new(f));
+        self
+    }
+
+    //Adds an extra window (ex: a second camera or a texture inspector) that gets opened along with the main one
+    //It shares the context's device and queue, so everything created in init can be used to render into it
+    //ctx.color_attachment/depth_attachment and the ui belong to the main window, and input events of viewports are ignored
+    //Closing a viewport's window only closes that viewport, see Context::open_viewport to reopen it
+    pub fn with_viewport(mut self,label: &str,config: WindowConfig,f: impl FnMut(&mut T,&Context,&Viewport,Frame) + 'static) -> Self{
+        self.viewports.push(ViewportEntry{
+            label: label.to_string(),
+            config,
+            render_fn: Box::new(f),
+            viewport: None,
+            open_on_start: true,
+        });
+        self
+    }
+
+    //Called with every user event, sent by jobs or through the app's proxy
+    pub fn on_user_event(mut self,f: impl FnMut(&mut T,&Context,E,&EventHandler<E>) + 'static) -> Self{
+        self.on_user_event_fn = Some(Box::new(f));
+        self
+    }
+
+    //Proxy of the event loop user events get sent to, only needed when embedding the app (run creates its own)
+    pub fn with_event_proxy(mut self,proxy: EventLoopProxy<E>) -> Self{
+        self.jobs = Some(Jobs::new(proxy));
+        self
+    }
+
+    //Called after a viewport's surface was resized (viewport.size is the new size)
+    pub fn on_viewport_resize(mut self,f: impl FnMut(&mut T,&Context,&Viewport) + 'static) -> Self{
+        self.on_viewport_resize_fn = Some(Box::new(f));
+        self
+    }
+
+    //Called right before a viewport's window gets closed
+    pub fn on_viewport_close(mut self,f: impl FnMut(&mut T,&Context,&Viewport) + 'static) -> Self{
+        self.on_viewport_close_fn = Some(Box::new(f));
+        self
+    }
+
     pub fn with_title(mut self,title: String) -> Self{
         self.title = title;
         self
     }
 
-    async fn inner_run<'a>(mut self,window:&'a winit::window::Window,event_loop: winit::event_loop::EventLoop<()>){
+    //Size,fullscreen,icon... of the window created in run()
+    pub fn with_window(mut self,config: WindowConfig) -> Self{
+        self.window_config = config;
+        self
+    }
+
+    //Pressing key saves the current frame as a png into dir (see Context::request_screenshot)
+    //The key is bound to the "screenshot" action, unless the action map already binds it (see ScreenshotPlugin)
+    pub fn with_screenshot_key(self,key: Key,dir: impl Into<PathBuf>) -> Self{
+        self.add_plugin(ScreenshotPlugin::new(key,dir))
+    }
+
+    //Registers a plugin, see plugin::Plugin for the hooks it gets
+    pub fn add_plugin(mut self,plugin: impl Plugin<T,E> + 'static) -> Self{
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    //Input bindings available through ctx.input (defaults to ActionMap::default())
+    //Binding the "quit" action makes the app exit when it's pressed
+    pub fn with_actions(mut self,actions: ActionMap) -> Self{
+        self.actions = actions;
+        self
+    }
+
+    //Loads the input bindings from a .toml/.ron file when running, or saves the current ones there if it doesn't exist yet
+    pub fn with_actions_file(mut self,path: impl Into<PathBuf>) -> Self{
+        self.actions_file = Some(path.into());
+        self
+    }
+
+    //Records the first frames of the run to disk (png sequence or gif), then exits
+    //Running fails if there's no render function, since only presented frames get recorded
+    pub fn record(mut self,settings: RecordSettings) -> Self{
+        self.record_settings = Some(settings);
+        self
+    }
+
+    //Enables input methods (composing text for languages like japanese/chinese), the committed text goes to on_text
+    pub fn with_ime(mut self,ime: bool) -> Self{
+        self.ime = ime;
+        self
+    }
+
+    //Records every input event and frame time step, saved as ron to path when the app exits (see replay_input)
+    pub fn record_input(mut self,path: impl Into<PathBuf>) -> Self{
+        self.input_record_path = Some(path.into());
+        self
+    }
+
+    //Drives the run with a recording made by record_input instead of live input, exits when it's done
+    //Frames use the recorded time steps, so with fixed updates the run plays out the same way again
+    //(live input is dropped before it reaches egui, so the ui gets no input at all during a replay)
+    pub fn replay_input(mut self,path: impl Into<PathBuf>) -> Self{
+        self.input_replay_path = Some(path.into());
+        self
+    }
+
+    //Customize backends,features,limits,present mode... of the context created in run()
+    pub fn with_context(mut self,builder: ContextBuilder) -> Self{
+        self.context_builder = builder;
+        self
+    }
+
+    async fn inner_run(mut self,window: &'window winit::window::Window,event_loop: winit::event_loop::EventLoop<E>) -> Result<()>{
         
+        self.attach_async(window).await?;
 
+        //errors inside the loop stop it, and get returned once it exits
+        let mut error: Option<Error> = None;
+        let error_ref = &mut error;
+        
+        event_loop.run(move |event,control_flow|{
+            if let Err(e) = self.process_event(event,control_flow){
+                *error_ref = Some(e);
+                control_flow.exit();
+            }
+            if self.exit_requested(){
+                control_flow.exit();
+            }
+        })?;
 
+        match error{
+            Some(e) => Err(e),
+            None => Ok(())
+        }
+    }
+
+    async fn attach_async(&mut self,window: &'window winit::window::Window) -> Result<()>{
         //Create a context here
-        let context = crate::base::context::Context::new(window).await;
+        let context = self.context_builder.build(window).await?;
         self.context = Some(context);
 
-        self.window = Some(&window);
-        
-        event_loop.run(move |event,control_flow|{
+        self.window = Some(window);
+        window.set_ime_allowed(self.ime);
 
-            let mut ui_handle = false;
-            if let Some(ui) = self.ui.as_mut(){
-                ui.platform.handle_event(&event);
-                if ui.platform.captures_event(&event) {
-                    ui_handle = true;
-                }
+        self.setup()
+    }
+
+    async fn inner_run_headless(mut self,width: u32,height: u32,frames: u64) -> Result<()>{
+        let context = self.context_builder.build_headless(width,height,HEADLESS_FORMAT).await?;
+        self.context = Some(context);
+
+        //fixed time steps so runs are reproducible (recording settings still take precedence)
+        self.context.as_mut().unwrap().simulated_frame_delta = Some(HEADLESS_FRAME_DELTA);
+        if self.input_replay_path.take().is_some(){
+            log::warn!("Input replay needs a window, ignoring it in the headless run");
+        }
+        if !self.viewports.is_empty(){
+            log::warn!("Viewports need a window, they aren't rendered in the headless run");
+        }
+        self.setup()?;
+        self.start();
+
+        let mut result = Ok(());
+        for _ in 0..frames{
+            result = self.redraw(None);
+            self.end_frame();
+            if result.is_err() || self.exit_requested(){
+                break;
             }
-            
-            if !ui_handle{
+        }
+
+        //still shut down cleanly after an error, but report the error first
+        let shutdown = self.shutdown();
+        result.and(shutdown)
+    }
+
+    //Everything that needs the context before the first frame
+    fn setup(&mut self) -> Result<()>{
+        self.context.as_mut().unwrap().time.set_fixed_delta(self.fixed_timestep);
+
+        //plugins add their bindings to the app's, the actions file (if there is one) replaces all of them
+        let ctx = self.context.as_mut().unwrap();
+        ctx.input.set_actions(self.actions.clone());
+        for plugin in self.plugins.iter_mut(){
+            plugin.init(ctx);
+        }
+        if let Some(path) = &self.actions_file{
+            let bindings = ActionMap::load_or_save(path,ctx.input.actions().clone())?;
+            ctx.input.set_actions(bindings);
+        }
+
+        if self.input_record_path.is_some(){
+            self.input_recorder = Some(InputRecorder::new());
+        }
+        if let Some(path) = &self.input_replay_path{
+            self.input_replay = Some(InputReplay::new(InputRecording::load(path)?));
+        }
+
+        if let Some(settings) = self.record_settings.take(){
+            //frames only get captured once they're presented, without a render fn the recording would never end
+            if self.render_fn.is_none() && self.render_with_ui_fn.is_none(){
+                return Err(Error::InvalidConfig("recording needs a render or render_with_ui function".to_string()));
+            }
+            let recorder = Recorder::new(settings)?;
+            self.context.as_mut().unwrap().simulated_frame_delta = Some(recorder.settings().frame_delta());
+            self.recorder = Some(recorder);
+        }
+
+        Ok(())
+    }
+
+    //Creates the ui and the app state (only the first time the app gets resumed)
+    fn start(&mut self){
+        //only create ui if the user specifies a ui render function
+        if self.render_with_ui_fn.is_some() && self.ui.is_none(){
+            let ctx = self.context.as_ref().unwrap();
+            self.ui = Some(ui::Ui::new(ctx,ctx.scale_factor));
+        }
+
+        //init app state
+        if self.state.is_some(){
+            return;
+        }
+        if let Some(mut init) = self.init_fn.take(){
+            self.state = Some(init(self,self.context.as_ref().unwrap()));
+            self.init_fn = Some(init);
+        }
+    }
+
+    //Per frame cleanup after redraw
+    fn end_frame(&mut self){
+        //just pressed/released and deltas only last for one frame
+        let ctx = self.context.as_mut().unwrap();
+        ctx.input.end_frame();
+        if let Some(recorder) = self.input_recorder.as_mut(){
+            recorder.end_frame(ctx.time.real_delta());
+        }
+    }
+
+    //Calls on_exit and writes out any recordings
+    fn shutdown(&mut self) -> Result<()>{
+        let ctx = self.context.as_ref().unwrap();
+        if let (Some(f),Some(state)) = (self.on_exit_fn.as_mut(),self.state.as_mut()){
+            f(state,ctx);
+        }
+        if let Some(state) = self.state.as_mut(){
+            for plugin in self.plugins.iter_mut(){
+                plugin.on_exit(state,ctx);
+            }
+        }
+        if let (Some(recorder),Some(path)) = (self.input_recorder.take(),&self.input_record_path){
+            recorder.finish().save(path)?;
+            log::info!("Saved input recording to {}",path.display());
+        }
+        //exited before every frame was recorded
+        if let Some(recorder) = self.recorder.take(){
+            recorder.finish(ctx)?;
+        }
+        Ok(())
+    }
+
+    fn process_event(&mut self,event: Event<E>,control_flow: &EventHandler<E>) -> Result<()>{
+        let result = self.dispatch_event(event,control_flow);
+        //viewports opened/closed by callbacks
+        let requests = self.process_viewport_requests(control_flow);
+        result.and(requests)
+    }
+
+    fn dispatch_event(&mut self,event: Event<E>,control_flow: &EventHandler<E>) -> Result<()>{
+
+        //events of the viewports' windows
+        if let Event::WindowEvent { window_id, event } = &event{
+            let index = self.viewports.iter().position(|entry| entry.viewport.as_ref().is_some_and(|viewport| viewport.id() == *window_id));
+            if let Some(index) = index{
+                return self.handle_viewport_event(index,event);
+            }
+        }
+
+        //events of other windows (ex: when embedded in a host application)
+        if let (Event::WindowEvent { window_id, .. },Some(win)) = (&event,self.window){
+            if *window_id != win.id(){
+                return Ok(());
+            }
+        }
+
+        //while replaying, the recording is the only source of input
+        if self.input_replay.is_some() && is_input_event(&event){
+            return Ok(());
+        }
+
+        if let Some(ui) = self.ui.as_mut(){
+            ui.platform.handle_event(&event);
+            if ui.platform.captures_event(&event) {
+                return Ok(());
+            }
+        }
+
+        match event{
+            Event::WindowEvent { event, .. } => {
+                if let Some(state) = self.state.as_mut(){
+                    for plugin in self.plugins.iter_mut(){
+                        plugin.on_window_event(state,self.context.as_ref().unwrap(),&event,control_flow);
+                    }
+                }
+                for input_event in InputEvent::from_window_event(&event,self.ime){
+                    self.dispatch_input(input_event,control_flow);
+                }
                 match event{
-                    Event::WindowEvent { window_id, event } => {
-                        match event{
-                            winit::event::WindowEvent::CloseRequested => {control_flow.exit()},
-                            winit::event::WindowEvent::KeyboardInput {event: KeyEvent{ physical_key: Code(key),state,..},..} => {
-                                if let Some(f) = self.on_key_fn{
-                                    if let Some(app_state) = self.state.as_mut(){
-                                        f(app_state,key,state,control_flow);
-                                    }
-                                    
-                                }
-                            },
-                            winit::event::WindowEvent::CursorMoved { position ,..} => {
-                                if let Some(f) = self.on_cursor_move_fn{
-                                    if let Some(state) = self.state.as_mut(){
-                                        if let Some(win) = self.window{
-                                            let size =  win.inner_size();
-                                            f(state,position,size,control_flow);
-                                        }
-                                        
-                                    }
-                                    
-                                }
-                            }
-                            winit::event::WindowEvent::MouseInput { state, button,.. } => {
-                                if let Some(f) = self.on_mouse_input_fn{
-                                    if let Some(app_state) = self.state.as_mut(){
-                                        f(app_state,button,state,control_flow);
-                                    }
-                                    
-                                }
-                            }
-                            winit::event::WindowEvent::Resized(size @ PhysicalSize{width,height}) => {
-                                println!("{}",&format!("Resized : ({width},{height})")[..].green());
-                                if width > 0 && height > 0{
-                                    let ctx = self.context.as_mut().unwrap();
-                                    ctx.size = size;
-                                    ctx.config.width = width;
-                                    ctx.config.height = height;
-                                    ctx.surface.configure(&ctx.device,&ctx.config);
-                                }
-                                if let Some(win) = &self.window{
-                                    win.request_redraw();
-                                }
-                                //call user provided resize function
-                                if let Some(f) = self.on_window_resize{
-                                    if let Some(state) = self.state.as_mut(){
-                                        f(state,self.context.as_ref().unwrap(), width,height);
-                                    }
-                                }
-                            }
-                            winit::event::WindowEvent::RedrawRequested => {
-                                if let Some(win) = &self.window{
-                                    win.request_redraw();
-                                    
-                                }
-                
-                                //call update
-                                if let Some(f) = self.update_fn{
-                                    if let Some(state) = self.state.as_mut(){
-                                        f(state,self.context.as_ref().unwrap());
-                                    }
-                                }
-                
-                                //call render
-                                if let Some(f) = self.render_fn{
-                                    if let Some(state) = self.state.as_ref(){
-                                        let surface_texture = self.context.as_ref().unwrap().surface.get_current_texture().unwrap();
-                                        f(state,self.context.as_ref().unwrap(),surface_texture);
-                                    }
-                                }
-                
-                                //call render with ui (only one will actually be called)
-                                if let Some(f) = self.render_with_ui_fn{
-                                    if let Some(state) = self.state.as_mut(){
-                                        let surface_texture = self.context.as_ref().unwrap().surface.get_current_texture().unwrap();
-                                        f(state,self.ui.as_mut().unwrap(), self.context.as_ref().unwrap(),surface_texture);
-                                    }
-                                }
-                            },
-                            winit::event::WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                                //TODO: inform egui
-                            }
-                            _ => {}
+                    winit::event::WindowEvent::CloseRequested => {
+                        if let Some(ctx) = self.context.as_ref(){
+                            ctx.request_exit();
                         }
                     },
-                    Event::DeviceEvent { device_id, event } => {
-                        match event{
-                            DeviceEvent::MouseMotion {delta} => {
-                                if let Some(f) = self.on_mouse_move_fn{
-                                    if let Some(state) = self.state.as_mut(){
-                                        f(state,(delta.0 as f32,delta.1 as f32),control_flow);
-                                    }
-                                    
-                                }
-                            },
-                            _ => {}
+                    winit::event::WindowEvent::DroppedFile(path) => {
+                        if let (Some(f),Some(state)) = (self.on_file_drop_fn.as_mut(),self.state.as_mut()){
+                            f(state,path,control_flow);
                         }
-                    },
-                    Event::Resumed => {
-                        //create ui here, only create ui if the user specifies a ui render function
-                        if let Some(_) = self.render_with_ui_fn{
-                            self.ui = Some(ui::Ui::new(self.context.as_ref().unwrap(),self.window.unwrap().scale_factor()));
+                    }
+                    winit::event::WindowEvent::Occluded(occluded) => {
+                        self.occluded = occluded;
+                        //the redraw loop stopped while hidden, start it again
+                        if let (false,Some(win)) = (occluded,self.window){
+                            win.request_redraw();
                         }
-
-                        //init app state
-                        self.state = Some((self.init_fn)(&self,self.context.as_ref().unwrap()));
+                        if let (Some(f),Some(state)) = (self.on_occluded_fn.as_mut(),self.state.as_mut()){
+                            f(state,occluded,control_flow);
+                        }
+                    }
+                    winit::event::WindowEvent::Resized(size @ PhysicalSize{width,height}) => {
+                        log::debug!("Resized : ({width},{height})");
+                        if let Some(recorder) = self.input_recorder.as_mut(){
+                            recorder.push(RecordedEvent::Resized(width,height));
+                        }
+                        self.context.as_mut().unwrap().resize(size);
+                        if let Some(win) = &self.window{
+                            win.request_redraw();
+                        }
+                        //call user provided resize function
+                        if let (Some(f),Some(state)) = (self.on_window_resize.as_mut(),self.state.as_mut()){
+                            f(state,self.context.as_ref().unwrap(), width,height);
+                        }
+                    }
+                    winit::event::WindowEvent::RedrawRequested => {
+                        //stop the redraw loop while hidden, resizing/becoming visible restarts it
+                        if self.is_hidden(){
+                            self.hidden = true;
+                            return Ok(());
+                        }
+                        if std::mem::take(&mut self.hidden){
+                            self.context.as_mut().unwrap().time.reset_frame_clock();
+                        }
+                        if let Some(win) = &self.window{
+                            win.request_redraw();
+                        }
+                        self.render_frame(control_flow)?;
                     },
+                    winit::event::WindowEvent::ScaleFactorChanged { scale_factor,.. } => {
+                        log::debug!("Scale factor changed: {scale_factor}");
+                        let ctx = self.context.as_mut().unwrap();
+                        ctx.scale_factor = scale_factor;
+                        if let Some(ui) = self.ui.as_mut(){
+                            ui.set_scale_factor(ctx,scale_factor);
+                        }
+                        if let (Some(f),Some(state)) = (self.on_scale_factor_changed_fn.as_mut(),self.state.as_mut()){
+                            f(state,self.context.as_ref().unwrap(),scale_factor);
+                        }
+                    }
                     _ => {}
                 }
+            },
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion {delta}, .. } => {
+                self.dispatch_input(InputEvent::MouseMotion { dx: delta.0 as f32, dy: delta.1 as f32 },control_flow);
+            },
+            Event::UserEvent(user_event) => {
+                if let (Some(f),Some(state)) = (self.on_user_event_fn.as_mut(),self.state.as_mut()){
+                    f(state,self.context.as_ref().unwrap(),user_event,control_flow);
+                }
+            },
+            Event::Resumed => {
+                self.start();
+                for entry in self.viewports.iter_mut().filter(|entry| entry.open_on_start){
+                    entry.open_on_start = false;
+                    self.context.as_ref().unwrap().open_viewport(&entry.label);
+                }
+            },
+            Event::LoopExiting => {
+                self.shutdown()?;
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn handle_viewport_event(&mut self,index: usize,event: &WindowEvent) -> Result<()>{
+        match event{
+            WindowEvent::CloseRequested => {
+                let label = self.viewports[index].label.clone();
+                self.close_viewport(&label);
+            },
+            WindowEvent::Resized(size) => {
+                let ctx = self.context.as_ref().unwrap();
+                if let Some(viewport) = self.viewports[index].viewport.as_mut(){
+                    viewport.resize(&ctx.device,*size);
+                    viewport.window.request_redraw();
+                    if let (Some(f),Some(state)) = (self.on_viewport_resize_fn.as_mut(),self.state.as_mut()){
+                        f(state,ctx,viewport);
+                    }
+                }
+            },
+            WindowEvent::RedrawRequested => {
+                self.render_viewport(index)?;
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    //Renders one viewport, update only runs with the main window's frames
+    fn render_viewport(&mut self,index: usize) -> Result<()>{
+        let ctx = self.context.as_ref().unwrap();
+        let entry = &mut self.viewports[index];
+        let (viewport,state) = match (entry.viewport.as_ref(),self.state.as_mut()){
+            (Some(viewport),Some(state)) => (viewport,state),
+            _ => return Ok(())
+        };
+
+        //same as the main window, minimized windows stop their redraw loop until resized
+        if viewport.size.width == 0 || viewport.size.height == 0 || viewport.window.is_minimized() == Some(true){
+            return Ok(());
+        }
+        viewport.window.request_redraw();
+
+        let surface_texture = match viewport.acquire_frame(&ctx.device)?{
+            Some(surface_texture) => surface_texture,
+            None => return Ok(())
+        };
+        let (frame,presented) = Frame::new(FrameTexture::Surface(surface_texture));
+        (entry.render_fn)(state,ctx,viewport,frame);
+
+        let presented = presented.borrow_mut().take();
+        if let Some(FrameTexture::Surface(surface_texture)) = presented{
+            surface_texture.present();
+        }
+        Ok(())
+    }
+
+    //Opens/closes the viewports requested through the context
+    fn process_viewport_requests(&mut self,control_flow: &EventHandler<E>) -> Result<()>{
+        let requests = match self.context.as_ref(){
+            Some(ctx) => ctx.take_viewport_requests(),
+            None => return Ok(())
+        };
+        for request in requests{
+            match request{
+                ViewportRequest::Open(label) => self.open_viewport(control_flow,&label)?,
+                ViewportRequest::Close(label) => self.close_viewport(&label),
+            }
+        }
+        Ok(())
+    }
+
+    //Runs a full frame (replayed input, update, render), normally done when the window requests a redraw
+    pub fn render_frame(&mut self,control_flow: &EventHandler<E>) -> Result<()>{
+        self.replay_frame(control_flow);
+        let result = self.redraw(Some(control_flow));
+        self.end_frame();
+        result
+    }
+
+    //Updates the input state and calls the input callbacks (for live and replayed input)
+    fn dispatch_input(&mut self,event: InputEvent,control_flow: &EventHandler<E>){
+        if let Some(recorder) = self.input_recorder.as_mut(){
+            recorder.push(RecordedEvent::Input(event.clone()));
+        }
+        self.context.as_mut().unwrap().input.handle_event(&event);
+
+        let state = match self.state.as_mut(){
+            Some(state) => state,
+            None => return
+        };
+        for plugin in self.plugins.iter_mut(){
+            plugin.on_input(state,self.context.as_ref().unwrap(),&event,control_flow);
+        }
+        match event{
+            InputEvent::Key { key, state: key_state } => {
+                if let Some(f) = self.on_key_fn.as_mut(){
+                    f(state,key,key_state,control_flow);
+                }
+            },
+            InputEvent::Text(text) => {
+                if let Some(f) = self.on_text_fn.as_mut(){
+                    f(state,&text,control_flow);
+                }
+            },
+            InputEvent::MouseInput { button, state: button_state } => {
+                if let Some(f) = self.on_mouse_input_fn.as_mut(){
+                    f(state,button,button_state,control_flow);
+                }
+            },
+            InputEvent::CursorMoved { x, y } => {
+                if let (Some(f),Some(win)) = (self.on_cursor_move_fn.as_mut(),self.window){
+                    f(state,Position::new(x,y),win.inner_size(),control_flow);
+                }
+            },
+            InputEvent::MouseMotion { dx, dy } => {
+                if let Some(f) = self.on_mouse_move_fn.as_mut(){
+                    f(state,(dx,dy),control_flow);
+                }
+            },
+            InputEvent::Scroll { dx, dy } => {
+                if let Some(f) = self.on_scroll_fn.as_mut(){
+                    f(state,(dx,dy),control_flow);
+                }
+            },
+            InputEvent::Focused(focused) => {
+                if let Some(f) = self.on_focus_fn.as_mut(){
+                    f(state,focused,control_flow);
+                }
+            },
+            InputEvent::CursorLeft | InputEvent::Modifiers(_) => {}
+        }
+    }
+
+    //Feeds the replayed events of the coming frame and uses its recorded time step, exits once the replay is done
+    fn replay_frame(&mut self,control_flow: &EventHandler<E>){
+        let replay = match self.input_replay.as_mut(){
+            Some(replay) => replay,
+            None => return
+        };
+        if replay.is_finished(){
+            log::info!("Input replay finished after {} frames",replay.frame());
+            self.context.as_ref().unwrap().request_exit();
+            return;
+        }
+
+        let (events,delta) = replay.next_frame();
+        self.context.as_mut().unwrap().simulated_frame_delta = delta;
+        for event in events{
+            match event{
+                RecordedEvent::Input(event) => self.dispatch_input(event,control_flow),
+                RecordedEvent::Resized(width,height) => {
+                    if let Some(win) = self.window{
+                        let _ = win.request_inner_size(PhysicalSize::new(width,height));
+                    }
+                },
+            }
+        }
+    }
+
+    //Minimized (zero sized) or occluded windows aren't rendered
+    fn is_hidden(&self) -> bool{
+        let minimized = match self.window{
+            Some(win) => {
+                let size = win.inner_size();
+                size.width == 0 || size.height == 0 || win.is_minimized() == Some(true)
+            },
+            None => false
+        };
+        self.occluded || minimized
+    }
+
+    //Runs one frame: update, render, then screenshots/recording of the presented frame
+    //control_flow is none when running headless
+    fn redraw(&mut self,control_flow: Option<&EventHandler<E>>) -> Result<()>{
+
+        //recreate the device (and everything that depends on it) if it was lost
+        if self.context.as_ref().unwrap().is_device_lost(){
+            let ctx = self.context.as_mut().unwrap();
+            pollster::block_on(ctx.recreate_device())?;
+            for viewport in self.viewports.iter().filter_map(|entry| entry.viewport.as_ref()){
+                viewport.reconfigure(&ctx.device);
+            }
+            if self.ui.is_some(){
+                self.ui = Some(ui::Ui::new(ctx,ctx.scale_factor));
+            }
+            if let (Some(f),Some(state)) = (self.on_device_recreated_fn.as_mut(),self.state.as_mut()){
+                f(state,ctx);
+            }
+        }
+
+        //actions hydra handles itself
+        let ctx = self.context.as_mut().unwrap();
+        if ctx.input.is_action_just_pressed(actions::QUIT){
+            ctx.request_exit();
+        }
+
+        //advance time, then run the fixed updates that are due
+        let fixed_steps = ctx.time.tick(ctx.simulated_frame_delta);
+        if let Some(state) = self.state.as_mut(){
+            for plugin in self.plugins.iter_mut(){
+                plugin.pre_update(state,ctx);
+            }
+        }
+        if let (Some(f),Some(state)) = (self.fixed_update_fn.as_mut(),self.state.as_mut()){
+            for _ in 0..fixed_steps{
+                f(state,ctx);
+            }
+        }
+
+        //call update
+        if let (Some(f),Some(state)) = (self.update_fn.as_mut(),self.state.as_mut()){
+            f(state,self.context.as_ref().unwrap());
+        }
+        if let Some(state) = self.state.as_mut(){
+            for plugin in self.plugins.iter_mut(){
+                plugin.post_update(state,self.context.as_ref().unwrap());
+            }
+        }
+
+        if self.render_fn.is_none() && self.render_with_ui_fn.is_none(){
+            return Ok(());
+        }
+
+        let ctx = self.context.as_mut().unwrap();
+        let frame_texture = if ctx.is_headless(){
+            //the offscreen target is lent to the frame, and given back once it's presented
+            match ctx.offscreen.take(){
+                Some(offscreen) => FrameTexture::Offscreen(offscreen.texture),
+                None => return Ok(())
+            }
+        }
+        else{
+            match ctx.acquire_frame(){
+                Ok(Some(surface_texture)) => FrameTexture::Surface(surface_texture),
+                //surface isn't ready, skip this frame
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    return match (self.on_out_of_memory_fn.as_mut(),self.state.as_mut(),control_flow){
+                        (Some(f),Some(state),Some(control_flow)) => {
+                            f(state,ctx,control_flow);
+                            Ok(())
+                        },
+                        _ => Err(e)
+                    };
+                }
+            }
+        };
+
+        let ctx = self.context.as_ref().unwrap();
+        let (frame,presented) = Frame::new(frame_texture);
+        if let Some(state) = self.state.as_mut(){
+            for plugin in self.plugins.iter_mut(){
+                plugin.pre_render(state,ctx);
+            }
+        }
+
+        //call render
+        if let Some(f) = self.render_fn.as_mut(){
+            if let Some(state) = self.state.as_mut(){
+                f(state,ctx,frame);
+            }
+        }
+        //call render with ui (only one will actually be called)
+        else if let Some(f) = self.render_with_ui_fn.as_mut(){
+            if let Some(state) = self.state.as_mut(){
+                f(state,self.ui.as_mut().unwrap(),ctx,frame);
+            }
+        }
+
+        //frame was presented by the user, take any screenshots and actually present it
+        let presented = presented.borrow_mut().take();
+        if let Some(texture) = presented{
+            if let Some(state) = self.state.as_mut(){
+                for plugin in self.plugins.iter_mut(){
+                    plugin.post_render(state,ctx,&texture);
+                }
+            }
+            if let Some(path) = ctx.take_screenshot_request(){
+                match crate::base::capture::save_png(ctx,&texture,&path){
+                    Ok(()) => log::info!("Saved screenshot to {}",path.display()),
+                    Err(e) => log::error!("Screenshot failed: {e}"),
+                }
             }
-        }).unwrap();
+            if let Some(recorder) = self.recorder.as_mut(){
+                recorder.capture(ctx,&texture)?;
+            }
+            if let (Some(f),Some(state)) = (self.on_frame_fn.as_mut(),self.state.as_mut()){
+                f(state,ctx,&texture);
+            }
+            match texture{
+                FrameTexture::Surface(surface_texture) => surface_texture.present(),
+                FrameTexture::Offscreen(texture) => self.context.as_mut().unwrap().restore_offscreen(texture),
+            }
+        }
+        //a frame that wasn't presented took the offscreen target with it
+        let ctx = self.context.as_mut().unwrap();
+        if ctx.is_headless() && ctx.offscreen.is_none(){
+            ctx.restore_offscreen(Texture::render_target(&ctx.device,ctx.config.width,ctx.config.height,ctx.config.format).texture);
+        }
+        let ctx = self.context.as_ref().unwrap();
+
+        //write out finished recording frames, stop once everything was recorded
+        if let Some(recorder) = self.recorder.as_mut(){
+            recorder.poll(ctx)?;
+            if recorder.is_complete(){
+                ctx.request_exit();
+                self.recorder.take().unwrap().finish(ctx)?;
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn run(self){
+    pub fn run(mut self) -> Result<()>{
         
-        let event_loop = winit::event_loop::EventLoop::new().unwrap();
+        let event_loop = EventLoopBuilder::<E>::with_user_event().build()?;
+        if self.jobs.is_none(){
+            self.jobs = Some(Jobs::new(event_loop.create_proxy()));
+        }
 
-        let window = WindowBuilder::new()
-            .with_title(&self.title)
-            .build(&event_loop).unwrap();
+        let window = self.window_config.builder(&self.title)?
+            .build(&event_loop)?;
+        self.window_config.apply(&window)?;
        
 
-        pollster::block_on(self.inner_run(&window,event_loop));
+        pollster::block_on(self.inner_run(&window,event_loop))
+    }
+
+    //Runs init, then update/render for a number of frames into an offscreen target without a window or event loop
+    //(ex: for tests on machines with only a software adapter, see ContextBuilder::with_fallback_adapter)
+    //Every frame advances time by 1/60s, frames can be inspected with on_frame or saved with record
+    pub fn run_headless(self,width: u32,height: u32,frames: u64) -> Result<()>{
+        pollster::block_on(self.inner_run_headless(width,height,frames))
+    }
+}
+
+
+
+
+
+//Events that are turned into InputEvents (ignored while replaying)
+fn is_input_event<E>(event: &Event<E>) -> bool{
+    match event{
+        Event::WindowEvent { event, .. } => !InputEvent::from_window_event(event,false).is_empty() || matches!(event,WindowEvent::KeyboardInput { .. }),
+        Event::DeviceEvent { event: DeviceEvent::MouseMotion { .. }, .. } => true,
+        _ => false
     }
 }
 
 
+#[cfg(test)]
+mod tests{
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
+    use super::*;
 
+    fn clear(ctx: &Context,frame: Frame,color: wgpu::Color){
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+            label: Some("my cmd encoder"),
+        });
+        {
+            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor{
+                label: Some("my render pass"),
+                color_attachments: &[Some(ctx.color_attachment(&view,wgpu::LoadOp::Clear(color)))],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+        ctx.queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+    }
+
+    #[test]
+    fn run_headless_renders_every_frame(){
+        let pixels = Rc::new(RefCell::new(vec![]));
+        let captured = pixels.clone();
+        App::new(|_,_| ())
+            .with_context(ContextBuilder::new().with_backends(wgpu::Backends::all()))
+            .render(|_,ctx,frame| clear(ctx,frame,wgpu::Color::RED))
+            .on_frame(move |_,ctx,texture|{
+                let image = ctx.capture_texture(texture).unwrap();
+                assert_eq!(image.dimensions(),(8,8));
+                captured.borrow_mut().push(image.get_pixel(7,7).0);
+            })
+            .run_headless(8,8,4)
+            .unwrap();
+        assert_eq!(*pixels.borrow(),[[255,0,0,255];4]);
+    }
+
+    #[test]
+    fn recording_needs_a_render_function(){
+        let dir = std::env::temp_dir().join(format!("hydra_record_no_render_{}",std::process::id()));
+        let result = App::new(|_,_| ())
+            .with_context(ContextBuilder::new().with_backends(wgpu::Backends::all()))
+            .record(RecordSettings::new(&dir))
+            .run_headless(8,8,1);
+        assert!(matches!(result,Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn run_headless_rejects_empty_targets(){
+        let result = App::new(|_,_| ())
+            .with_context(ContextBuilder::new().with_backends(wgpu::Backends::all()))
+            .run_headless(0,8,1);
+        assert!(matches!(result,Err(Error::InvalidSize { width: 0, height: 8 })));
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::base::context::Context;
+use crate::error::{Error, Result};
+
+//A texture -> buffer copy that was submitted, but whose data might not be mapped yet
+pub struct PendingCapture{
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+    mapped: mpsc::Receiver<std::result::Result<(),wgpu::BufferAsyncError>>,
+}
+
+impl PendingCapture{
+
+    //Copies the texture into a readback buffer and starts mapping it
+    pub fn new(ctx: &Context,texture: &wgpu::Texture) -> Result<Self>{
+        let format = texture.format();
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC){
+            return Err(Error::Capture(format!("{format:?} texture wasn't created with COPY_SRC usage")));
+        }
+        if !matches!(format,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb |
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb){
+            return Err(Error::Capture(format!("can't convert {format:?} to rgba8")));
+        }
+
+        let (width,height) = (texture.width(),texture.height());
+
+        //rows in the buffer have to be padded to a multiple of 256 bytes
+        let unpadded_bytes_per_row = 4 * width;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor{
+            label: Some("my capture buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+            label: Some("my capture encoder"),
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture{
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer{
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout{
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d{
+                width,
+                height,
+                depth_or_array_layers: 1,
+            }
+        );
+
+        ctx.queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender,mapped) = mpsc::channel();
+        buffer.slice(..).map_async(wgpu::MapMode::Read,move |result|{
+            let _ = sender.send(result);
+        });
+
+        Ok(PendingCapture{
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            format,
+            mapped,
+        })
+    }
+
+    //Returns the image if the gpu is done with the copy, doesn't block
+    pub fn try_finish(&self,device: &wgpu::Device) -> Option<Result<image::RgbaImage>>{
+        device.poll(wgpu::Maintain::Poll);
+        match self.mapped.try_recv(){
+            Ok(result) => Some(self.read(result)),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(Error::Capture("capture buffer was never mapped".to_string()))),
+        }
+    }
+
+    //Blocks until the copy is done
+    pub fn wait(self,device: &wgpu::Device) -> Result<image::RgbaImage>{
+        device.poll(wgpu::Maintain::Wait);
+        match self.mapped.recv(){
+            Ok(result) => self.read(result),
+            Err(_) => Err(Error::Capture("capture buffer was never mapped".to_string())),
+        }
+    }
+
+    fn read(&self,mapped: std::result::Result<(),wgpu::BufferAsyncError>) -> Result<image::RgbaImage>{
+        mapped.map_err(|e| Error::Capture(e.to_string()))?;
+
+        let unpadded_bytes_per_row = (4 * self.width) as usize;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        {
+            let data = self.buffer.slice(..).get_mapped_range();
+            for row in data.chunks(self.padded_bytes_per_row as usize){
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.buffer.unmap();
+
+        //bgra -> rgba, srgb formats are already stored gamma encoded which is what png expects
+        if let wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb = self.format{
+            for pixel in pixels.chunks_mut(4){
+                pixel.swap(0,2);
+            }
+        }
+
+        image::RgbaImage::from_raw(self.width,self.height,pixels)
+            .ok_or(Error::Capture("capture buffer has the wrong size".to_string()))
+    }
+}
+
+//Reads a texture back to the cpu (blocks until the gpu is done), the texture needs COPY_SRC usage
+pub fn capture_texture(ctx: &Context,texture: &wgpu::Texture) -> Result<image::RgbaImage>{
+    PendingCapture::new(ctx,texture)?.wait(&ctx.device)
+}
+
+//Reads a texture back and writes it out as a png
+//Missing directories of the path get created
+pub fn save_png(ctx: &Context,texture: &wgpu::Texture,path: impl AsRef<Path>) -> Result<()>{
+    let path = path.as_ref();
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()){
+        std::fs::create_dir_all(dir).map_err(|e| Error::Capture(format!("can't create {}: {e}",dir.display())))?;
+    }
+    capture_texture(ctx,texture)?
+        .save_with_format(path,image::ImageFormat::Png)
+        .map_err(Error::SaveImage)
+}
+
+//How recorded frames get written out
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum RecordFormat{
+    //frame_00000.png, frame_00001.png, ...
+    Png,
+    //a single recording.gif
+    Gif,
+}
+
+//Settings for recording an app run to disk (see App::record)
+//Every rendered frame counts as 1/fps seconds of the recording, no matter how long it actually took
+#[derive(Debug,Clone)]
+pub struct RecordSettings{
+    pub dir: PathBuf,
+    pub fps: u32,
+    pub frame_count: u32,
+    //if set, takes precedence over frame_count (resolved with the final fps, see frame_count())
+    pub duration: Option<Duration>,
+    pub format: RecordFormat,
+}
+
+impl RecordSettings{
+    //Defaults to 60 png frames at 30 fps
+    pub fn new(dir: impl Into<PathBuf>) -> Self{
+        Self{
+            dir: dir.into(),
+            fps: 30,
+            frame_count: 60,
+            duration: None,
+            format: RecordFormat::Png,
+        }
+    }
+
+    pub fn with_fps(mut self,fps: u32) -> Self{
+        self.fps = fps.max(1);
+        self
+    }
+
+    pub fn with_frame_count(mut self,frame_count: u32) -> Self{
+        self.frame_count = frame_count;
+        self.duration = None;
+        self
+    }
+
+    //Records this long instead of a frame count, at whatever fps ends up being set
+    pub fn with_duration(mut self,duration: Duration) -> Self{
+        self.duration = Some(duration);
+        self
+    }
+
+    //Number of frames to record
+    pub fn frame_count(&self) -> u32{
+        match self.duration{
+            Some(duration) => (duration.as_secs_f64() * self.fps as f64).round() as u32,
+            None => self.frame_count
+        }
+    }
+
+    pub fn with_format(mut self,format: RecordFormat) -> Self{
+        self.format = format;
+        self
+    }
+
+    //Simulated time between two recorded frames
+    pub fn frame_delta(&self) -> Duration{
+        Duration::from_secs_f64(1.0 / self.fps as f64)
+    }
+}
+
+//Background thread encoding (path,image) pairs to png
+type PngWriter = (mpsc::Sender<(PathBuf,image::RgbaImage)>,JoinHandle<Result<()>>);
+
+//Records frames without stalling rendering:
+//gpu readbacks are polled instead of waited on, and pngs get encoded on a separate thread
+pub struct Recorder{
+    settings: RecordSettings,
+    //frames submitted for capture so far
+    captured: u32,
+    //frames written out (or kept for the gif) so far
+    written: u32,
+    pending: VecDeque<PendingCapture>,
+    //png frames
+    png_writer: Option<PngWriter>,
+    //gif frames
+    gif_frames: Vec<image::RgbaImage>,
+}
+
+impl Recorder{
+    pub fn new(settings: RecordSettings) -> Result<Self>{
+        //fps is a pub field, so with_fps isn't the only way to set it
+        if settings.fps == 0{
+            return Err(Error::InvalidConfig("recording fps has to be at least 1".to_string()));
+        }
+        std::fs::create_dir_all(&settings.dir).map_err(|e| Error::Capture(format!("can't create {}: {e}",settings.dir.display())))?;
+
+        let png_writer = match settings.format{
+            RecordFormat::Png => {
+                let (sender,receiver) = mpsc::channel::<(PathBuf,image::RgbaImage)>();
+                let handle = std::thread::spawn(move ||{
+                    for (path,image) in receiver{
+                        image.save_with_format(&path,image::ImageFormat::Png).map_err(Error::SaveImage)?;
+                    }
+                    Ok(())
+                });
+                Some((sender,handle))
+            },
+            RecordFormat::Gif => None
+        };
+
+        Ok(Self{
+            settings,
+            captured: 0,
+            written: 0,
+            pending: VecDeque::new(),
+            png_writer,
+            gif_frames: vec![],
+        })
+    }
+
+    pub fn settings(&self) -> &RecordSettings{
+        &self.settings
+    }
+
+    //True once every frame of the recording was captured
+    pub fn is_complete(&self) -> bool{
+        self.captured >= self.settings.frame_count()
+    }
+
+    //Starts reading back a rendered frame (ignored once the recording is complete)
+    pub fn capture(&mut self,ctx: &Context,texture: &wgpu::Texture) -> Result<()>{
+        if self.is_complete(){
+            return Ok(());
+        }
+        self.pending.push_back(PendingCapture::new(ctx,texture)?);
+        self.captured += 1;
+        Ok(())
+    }
+
+    //Writes out every frame whose readback is done, in order
+    pub fn poll(&mut self,ctx: &Context) -> Result<()>{
+        while let Some(capture) = self.pending.front(){
+            match capture.try_finish(&ctx.device){
+                Some(image) => {
+                    self.pending.pop_front();
+                    self.write(image?)?;
+                },
+                None => break
+            }
+        }
+        Ok(())
+    }
+
+    //Waits for all pending frames and finishes writing the recording
+    pub fn finish(mut self,ctx: &Context) -> Result<()>{
+        while let Some(capture) = self.pending.pop_front(){
+            let image = capture.wait(&ctx.device)?;
+            self.write(image)?;
+        }
+
+        if let Some((sender,handle)) = self.png_writer.take(){
+            drop(sender);
+            handle.join().map_err(|_| Error::Capture("png writer thread panicked".to_string()))??;
+        }
+
+        if self.settings.format == RecordFormat::Gif{
+            let path = self.settings.dir.join("recording.gif");
+            let file = std::fs::File::create(&path).map_err(|e| Error::Capture(format!("can't create {}: {e}",path.display())))?;
+            let mut encoder = image::codecs::gif::GifEncoder::new(std::io::BufWriter::new(file));
+            encoder.set_repeat(image::codecs::gif::Repeat::Infinite).map_err(Error::SaveImage)?;
+            let delay = image::Delay::from_numer_denom_ms(1000,self.settings.fps);
+            encoder.encode_frames(self.gif_frames.drain(..).map(|frame| image::Frame::from_parts(frame,0,0,delay))).map_err(Error::SaveImage)?;
+        }
+
+        log::info!("Recorded {} frames to {}",self.written,self.settings.dir.display());
+        Ok(())
+    }
+
+    fn write(&mut self,image: image::RgbaImage) -> Result<()>{
+        match &self.png_writer{
+            Some((sender,_)) => {
+                let path = self.settings.dir.join(format!("frame_{:05}.png",self.written));
+                sender.send((path,image)).map_err(|_| Error::Capture("png writer thread stopped".to_string()))?;
+            },
+            None => self.gif_frames.push(image)
+        }
+        self.written += 1;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::base::context::test_context;
+
+    //every pixel is different so rows read with the wrong stride show up
+    fn test_image(width: u32,height: u32) -> image::RgbaImage{
+        image::RgbaImage::from_fn(width,height,|x,y| image::Rgba([x as u8 * 10,y as u8 * 20,100,255 - x as u8]))
+    }
+
+    //uploads the image into a texture of the given format, swapping to bgra if needed
+    fn upload(ctx: &Context,image: &image::RgbaImage,format: wgpu::TextureFormat) -> wgpu::Texture{
+        let (width,height) = image.dimensions();
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor{
+            label: Some("my capture test texture"),
+            size: wgpu::Extent3d{ width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let mut bytes = image.as_raw().clone();
+        if format == wgpu::TextureFormat::Bgra8Unorm{
+            for pixel in bytes.chunks_mut(4){
+                pixel.swap(0,2);
+            }
+        }
+        ctx.queue.write_texture(
+            wgpu::ImageCopyTexture{
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &bytes,
+            wgpu::ImageDataLayout{
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d{ width, height, depth_or_array_layers: 1 },
+        );
+        texture
+    }
+
+    #[test]
+    fn capture_removes_row_padding_and_swaps_bgra(){
+        let ctx = test_context(4,4,wgpu::TextureFormat::Rgba8Unorm);
+        //10 * 4 bytes per row, padded to 256 in the readback buffer
+        let image = test_image(10,3);
+        for format in [wgpu::TextureFormat::Rgba8Unorm,wgpu::TextureFormat::Bgra8Unorm]{
+            let texture = upload(&ctx,&image,format);
+            assert_eq!(capture_texture(&ctx,&texture).unwrap(),image,"{format:?}");
+        }
+    }
+
+    #[test]
+    fn save_png_creates_the_directory(){
+        let ctx = test_context(4,4,wgpu::TextureFormat::Rgba8Unorm);
+        let image = test_image(10,3);
+        let texture = upload(&ctx,&image,wgpu::TextureFormat::Rgba8Unorm);
+
+        let dir = std::env::temp_dir().join(format!("hydra_screenshots_{}",std::process::id()));
+        let path = dir.join("nested").join("screenshot.png");
+        save_png(&ctx,&texture,&path).unwrap();
+        assert_eq!(image::open(&path).unwrap().to_rgba8(),image);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recorder_rejects_zero_fps(){
+        let dir = std::env::temp_dir().join(format!("hydra_record_zero_fps_{}",std::process::id()));
+        let settings = RecordSettings{ fps: 0, ..RecordSettings::new(&dir) };
+        assert!(matches!(Recorder::new(settings),Err(Error::InvalidConfig(_))));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn duration_uses_the_final_fps(){
+        let settings = RecordSettings::new("frames").with_duration(Duration::from_secs(2)).with_fps(10);
+        assert_eq!(settings.frame_count(),20);
+        let settings = RecordSettings::new("frames").with_fps(10).with_duration(Duration::from_secs(2));
+        assert_eq!(settings.frame_count(),20);
+        //the last one wins
+        let settings = settings.with_frame_count(5);
+        assert_eq!(settings.frame_count(),5);
+    }
+}
@@ -1,98 +1,714 @@
-use colored::Colorize;
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use wgpu::{Backends, PowerPreference};
 
+use crate::base::adapter::{self, AdapterSelector};
+use crate::base::capture;
+use crate::base::input::Input;
+use crate::base::texture::Texture;
+use crate::base::time::Time;
+use crate::base::viewport::ViewportRequest;
+use crate::base::window::{self, FullscreenMode};
+use crate::error::{Error, Result};
+
 pub struct Context<'a>{
     pub instance: wgpu::Instance,
-    pub surface: wgpu::Surface<'a>,
+    //none when running headless
+    pub surface: Option<wgpu::Surface<'a>>,
+    pub window: Option<&'a winit::window::Window>,
     pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub size: winit::dpi::PhysicalSize<u32>,
+    //physical pixels per logical pixel of the window (1 when headless), kept up to date by the app
+    pub scale_factor: f64,
+    //for headless contexts this describes the offscreen target instead of a swapchain
     pub config: wgpu::SurfaceConfiguration,
+    //color target rendered into when there is no surface
+    pub offscreen: Option<Texture>,
+
+    //msaa, when sample_count > 1 there's a multisampled color target that gets resolved into the frame
+    pub sample_count: u32,
+    pub msaa_target: Option<Texture>,
+    //managed depth target (same sample count as the color target)
+    pub depth_target: Option<Texture>,
 
     init: bool,
+    //set from wgpu's device lost callback
+    device_lost: Arc<AtomicBool>,
+    //settings used to create the context, reused when the device needs to be recreated
+    builder: ContextBuilder,
+    //where to save the next presented frame
+    screenshot_request: RefCell<Option<PathBuf>>,
+    //viewports to open/close once the current event is handled
+    viewport_requests: RefCell<Vec<ViewportRequest>>,
+    //set by request_exit, the app stops after the current event/frame
+    exit_request: Cell<bool>,
+    //frame timing, advanced by the app every frame
+    pub time: Time,
+    //keyboard/mouse state, updated by the app from window events
+    pub input: Input,
+    //fixed time step per frame while recording
+    pub(crate) simulated_frame_delta: Option<Duration>,
 }
 
 impl<'a> Context<'a>{
-   
-    pub async fn new(window: &'a winit::window::Window) -> Context<'a>{
-        //instance
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor{
+
+    pub async fn new(window: &'a winit::window::Window) -> Result<Context<'a>>{
+        ContextBuilder::new().build(window).await
+    }
+
+    //Creates a context without a window, rendering goes to an offscreen color target instead
+    pub async fn new_headless(width: u32,height: u32,format: wgpu::TextureFormat) -> Result<Context<'static>>{
+        ContextBuilder::new().build_headless(width, height, format).await
+    }
+
+    pub fn init(&self) -> bool{
+        self.init
+    }
+
+    pub fn is_headless(&self) -> bool{
+        self.surface.is_none()
+    }
+
+    //Every adapter the instance can see for the given backends
+    pub fn enumerate_adapters(&self,backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo>{
+        adapter::enumerate_instance_adapters(&self.instance,backends)
+    }
+
+    //What the adapter (and surface, if there is one) supports
+    pub fn capabilities(&self) -> Capabilities{
+        let surface_capabilities = match &self.surface{
+            Some(surface) => surface.get_capabilities(&self.adapter),
+            None => wgpu::SurfaceCapabilities::default()
+        };
+
+        Capabilities{
+            adapter_info: self.adapter.get_info(),
+            features: self.adapter.features(),
+            limits: self.adapter.limits(),
+            surface_formats: surface_capabilities.formats,
+            present_modes: surface_capabilities.present_modes,
+            alpha_modes: surface_capabilities.alpha_modes,
+            surface_usages: surface_capabilities.usages,
+        }
+    }
+
+    //Reconfigures the surface (or recreates the offscreen target) for a new size
+    pub fn resize(&mut self,size: winit::dpi::PhysicalSize<u32>){
+        if size.width == 0 || size.height == 0{
+            return;
+        }
+        self.size = size;
+        self.config.width = size.width;
+        self.config.height = size.height;
+        if let Some(surface) = &self.surface{
+            surface.configure(&self.device,&self.config);
+        }
+        //the offscreen target belongs to the old device
+        self.offscreen = None;
+        self.recreate_targets();
+    }
+
+    //Changes the msaa sample count, recreating the managed color/depth targets
+    //Pipelines drawing into them need to be built with the same sample count
+    pub fn set_sample_count(&mut self,sample_count: u32) -> Result<()>{
+        check_sample_count(&self.adapter,self.config.format,sample_count)?;
+        if let Some(format) = self.builder.depth_format{
+            check_sample_count(&self.adapter,format,sample_count)?;
+        }
+        self.sample_count = sample_count;
+        self.recreate_targets();
+        Ok(())
+    }
+
+    //Color attachment for a frame's view, renders into the msaa target and resolves to the frame if msaa is on
+    pub fn color_attachment<'v>(&'v self,frame_view: &'v wgpu::TextureView,load: wgpu::LoadOp<wgpu::Color>) -> wgpu::RenderPassColorAttachment<'v>{
+        let ops = wgpu::Operations{
+            load,
+            store: wgpu::StoreOp::Store,
+        };
+        match &self.msaa_target{
+            Some(msaa) => wgpu::RenderPassColorAttachment{
+                view: &msaa.view,
+                resolve_target: Some(frame_view),
+                //only the resolved result is needed after the pass
+                ops: wgpu::Operations{ store: wgpu::StoreOp::Discard, ..ops },
+            },
+            None => wgpu::RenderPassColorAttachment{
+                view: frame_view,
+                resolve_target: None,
+                ops,
+            }
+        }
+    }
+
+    //Depth attachment for the managed depth target (if the context has one), cleared to 1.0
+    pub fn depth_attachment(&self) -> Option<wgpu::RenderPassDepthStencilAttachment<'_>>{
+        self.depth_target.as_ref().map(|depth| wgpu::RenderPassDepthStencilAttachment{
+            view: &depth.view,
+            depth_ops: Some(wgpu::Operations{
+                load: wgpu::LoadOp::Clear(1.0),
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        })
+    }
+
+    //Recreates every context owned target for the current size & sample count
+    fn recreate_targets(&mut self){
+        let (width,height) = (self.config.width,self.config.height);
+        //the offscreen target isn't multisampled, so it's only recreated when missing or the wrong size
+        let offscreen_outdated = self.offscreen.as_ref().is_none_or(|offscreen| offscreen.texture.width() != width || offscreen.texture.height() != height);
+        if self.surface.is_none() && offscreen_outdated{
+            self.offscreen = Some(Texture::render_target(&self.device,width,height,self.config.format));
+        }
+        self.msaa_target = match self.sample_count{
+            1 => None,
+            n => Some(Texture::attachment(&self.device,width,height,self.config.format,n)),
+        };
+        self.depth_target = self.builder.depth_format.map(|format| Texture::attachment(&self.device,width,height,format,self.sample_count));
+    }
+
+    //Reads a texture (with COPY_SRC usage) back to the cpu, blocks until the gpu is done
+    pub fn capture_texture(&self,texture: &wgpu::Texture) -> Result<image::RgbaImage>{
+        capture::capture_texture(self,texture)
+    }
+
+    //Saves the next frame presented by the app as a png
+    pub fn request_screenshot(&self,path: impl Into<PathBuf>){
+        *self.screenshot_request.borrow_mut() = Some(path.into());
+    }
+
+    pub(crate) fn take_screenshot_request(&self) -> Option<PathBuf>{
+        self.screenshot_request.borrow_mut().take()
+    }
+
+    //Opens the viewport added with App::with_viewport under this label (does nothing if it's already open)
+    pub fn open_viewport(&self,label: &str){
+        self.viewport_requests.borrow_mut().push(ViewportRequest::Open(label.to_string()));
+    }
+
+    //Closes the viewport's window, it can be opened again later
+    pub fn close_viewport(&self,label: &str){
+        self.viewport_requests.borrow_mut().push(ViewportRequest::Close(label.to_string()));
+    }
+
+    pub(crate) fn take_viewport_requests(&self) -> Vec<ViewportRequest>{
+        std::mem::take(&mut *self.viewport_requests.borrow_mut())
+    }
+
+    //Stops the app once the current event/frame is handled (same as the quit action)
+    pub fn request_exit(&self){
+        self.exit_request.set(true);
+    }
+
+    pub fn is_exit_requested(&self) -> bool{
+        self.exit_request.get()
+    }
+
+    //While the app is being recorded every frame advances time by exactly this much (1/fps)
+    pub fn simulated_frame_delta(&self) -> Option<Duration>{
+        self.simulated_frame_delta
+    }
+
+    //Switches the window between windowed and fullscreen (does nothing headless)
+    pub fn toggle_fullscreen(&self,mode: FullscreenMode){
+        if let Some(window) = self.window{
+            window::toggle_fullscreen(window,mode);
+        }
+    }
+
+    pub fn set_fullscreen(&self,mode: Option<FullscreenMode>){
+        if let Some(window) = self.window{
+            window::set_fullscreen(window,mode);
+        }
+    }
+
+    //Locks and hides the cursor (ex: for mouse look), see window::grab_cursor
+    pub fn grab_cursor(&self,grab: bool) -> Result<()>{
+        match self.window{
+            Some(window) => window::grab_cursor(window,grab),
+            None => Ok(())
+        }
+    }
+
+    //Puts a frame's offscreen texture back as the target (headless)
+    pub(crate) fn restore_offscreen(&mut self,texture: wgpu::Texture){
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.offscreen = Some(Texture{ texture, view });
+    }
+
+    //True if the device was lost (ex: driver reset) and needs to be recreated
+    pub fn is_device_lost(&self) -> bool{
+        self.device_lost.load(Ordering::SeqCst)
+    }
+
+    //Requests a new adapter,device and queue with the original settings and reconfigures the surface
+    //Everything created with the old device (pipelines,buffers,textures...) has to be recreated by the user
+    pub async fn recreate_device(&mut self) -> Result<()>{
+        let adapter = self.builder.request_adapter(&self.instance,self.surface.as_ref()).await?;
+        let (device,queue) = self.builder.request_device(&adapter).await?;
+
+        self.device_lost.store(false,Ordering::SeqCst);
+        watch_device_lost(&device,self.device_lost.clone());
+
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+
+        if let Some(surface) = &self.surface{
+            surface.configure(&self.device,&self.config);
+        }
+        //the offscreen target belongs to the old device
+        self.offscreen = None;
+        self.recreate_targets();
+
+        Ok(())
+    }
+
+    //Gets the next swapchain texture to render into
+    //Outdated/lost surfaces get reconfigured and retried, timeouts skip the frame (returns none),
+    //anything else (out of memory) is returned as an error
+    pub fn acquire_frame(&self) -> Result<Option<wgpu::SurfaceTexture>>{
+        let surface = match &self.surface{
+            Some(surface) => surface,
+            None => return Ok(None)
+        };
+
+        acquire_surface_frame(surface,&self.device,&self.config)
+    }
+
+
+}
+
+//Snapshot of adapter & surface capabilities (surface fields are empty for headless contexts)
+#[derive(Debug,Clone)]
+pub struct Capabilities{
+    pub adapter_info: wgpu::AdapterInfo,
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub surface_formats: Vec<wgpu::TextureFormat>,
+    pub present_modes: Vec<wgpu::PresentMode>,
+    pub alpha_modes: Vec<wgpu::CompositeAlphaMode>,
+    pub surface_usages: wgpu::TextureUsages,
+}
+
+impl Capabilities{
+    //Writes everything out through the log facade
+    pub fn log(&self,level: log::Level){
+        log::log!(level,"Adapter: {:#?}",self.adapter_info);
+        log::log!(level,"Adapter Features: {:#?}",self.features);
+        log::log!(level,"Adapter Limits: {:#?}",self.limits);
+        log::log!(level,"Surface [Present Modes]: {:#?}",self.present_modes);
+        log::log!(level,"Surface [Formats]: {:#?}",self.surface_formats);
+        log::log!(level,"Surface [Alpha Modes]: {:#?}",self.alpha_modes);
+        log::log!(level,"Surface [Usages]: {:#?}",self.surface_usages);
+    }
+}
+
+//Configures how the instance, adapter, device and surface get created
+#[derive(Debug,Clone)]
+pub struct ContextBuilder{
+    backends: wgpu::Backends,
+    power_preference: wgpu::PowerPreference,
+    force_fallback_adapter: bool,
+    //explicit adapter choice (HYDRA_ADAPTER takes priority over this)
+    adapter: Option<AdapterSelector>,
+    //context creation fails if these aren't supported
+    required_features: wgpu::Features,
+    //enabled only if the adapter supports them
+    optional_features: wgpu::Features,
+    limits: wgpu::Limits,
+    present_mode: wgpu::PresentMode,
+    frame_latency: u32,
+    //if none, prefer srgb
+    surface_format: Option<wgpu::TextureFormat>,
+    //level the adapter/surface capabilities get logged at
+    log_level: log::Level,
+    sample_count: u32,
+    depth_format: Option<wgpu::TextureFormat>,
+}
+
+impl Default for ContextBuilder{
+    fn default() -> Self {
+        Self {
             backends: Backends::PRIMARY,
-            ..Default::default()
-        });
+            power_preference: PowerPreference::default(),
+            force_fallback_adapter: false,
+            adapter: None,
+            required_features: wgpu::Features::default(),
+            optional_features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            present_mode: wgpu::PresentMode::Fifo,
+            frame_latency: 2,
+            surface_format: None,
+            log_level: log::Level::Debug,
+            sample_count: 1,
+            depth_format: None,
+        }
+    }
+}
+
+impl ContextBuilder{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    pub fn with_backends(mut self,backends: wgpu::Backends) -> Self{
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self,power_preference: wgpu::PowerPreference) -> Self{
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn with_fallback_adapter(mut self,force_fallback_adapter: bool) -> Self{
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn with_adapter(mut self,selector: AdapterSelector) -> Self{
+        self.adapter = Some(selector);
+        self
+    }
+
+    //Every adapter available for this builder's backends, see AdapterSelector::Index
+    pub fn enumerate_adapters(&self) -> Vec<wgpu::AdapterInfo>{
+        adapter::enumerate_adapters(self.backends)
+    }
+
+    pub fn with_required_features(mut self,features: wgpu::Features) -> Self{
+        self.required_features = features;
+        self
+    }
+
+    pub fn with_optional_features(mut self,features: wgpu::Features) -> Self{
+        self.optional_features = features;
+        self
+    }
+
+    pub fn with_limits(mut self,limits: wgpu::Limits) -> Self{
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_present_mode(mut self,present_mode: wgpu::PresentMode) -> Self{
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn with_frame_latency(mut self,frame_latency: u32) -> Self{
+        self.frame_latency = frame_latency;
+        self
+    }
+
+    pub fn with_surface_format(mut self,format: wgpu::TextureFormat) -> Self{
+        self.surface_format = Some(format);
+        self
+    }
+
+    pub fn with_log_level(mut self,level: log::Level) -> Self{
+        self.log_level = level;
+        self
+    }
+
+    //Renders into a multisampled color target (see Context::color_attachment) with this many samples
+    pub fn with_msaa(mut self,sample_count: u32) -> Self{
+        self.sample_count = sample_count;
+        self
+    }
+
+    //Has the context manage a depth target (see Context::depth_attachment), resized along with the surface
+    pub fn with_depth_target(mut self,format: wgpu::TextureFormat) -> Self{
+        self.depth_format = Some(format);
+        self
+    }
+
+    pub async fn build<'a>(&self,window: &'a winit::window::Window) -> Result<Context<'a>>{
+        //instance
+        let instance = self.create_instance();
 
         //surface
-        let surface = instance.create_surface(window).unwrap();
+        let surface = instance.create_surface(window).map_err(Error::CreateSurface)?;
 
         //adapter
-        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions{
-            power_preference: PowerPreference::default(),
-            force_fallback_adapter: false,
-            compatible_surface: Some(&surface),
-        }).await.unwrap();
+        let adapter = self.request_adapter(&instance,Some(&surface)).await?;
 
         //device
-        let (device,queue) = adapter.request_device(&wgpu::DeviceDescriptor{
-            label: Some("my device"),
-            required_features: wgpu::Features::default(),
-            required_limits: wgpu::Limits::default(),
-        },None).await.unwrap();
+        let device = self.request_device(&adapter).await?;
 
         //swapchain
         let surface_capabilities = surface.get_capabilities(&adapter);
 
-        //prefer srgb, otherwise get first one
-        let swapchain_format = surface_capabilities.formats.iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_capabilities.formats[0]);
+        if let Some(format) = self.surface_format{
+            if !surface_capabilities.formats.contains(&format){
+                return Err(Error::UnsupportedSurfaceFormat { requested: format, supported: surface_capabilities.formats });
+            }
+        }
 
-        let size = window.inner_size();
+        if !surface_capabilities.present_modes.contains(&self.present_mode){
+            return Err(Error::UnsupportedPresentMode { requested: self.present_mode, supported: surface_capabilities.present_modes });
+        }
 
+        let config = surface_config(&surface_capabilities,self.surface_format,self.present_mode,self.frame_latency,window.inner_size());
 
-        let config = wgpu::SurfaceConfiguration{
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: swapchain_format,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
-            desired_maximum_frame_latency: 2,
-            alpha_mode: surface_capabilities.alpha_modes[0],
-            view_formats: vec![],
-        };
+        self.create_context(instance,adapter,device,Some((surface,window)),config,None)
+    }
 
-        surface.configure(&device, &config);
+    //Builds a context with no surface, see Context::new_headless
+    pub async fn build_headless(&self,width: u32,height: u32,format: wgpu::TextureFormat) -> Result<Context<'static>>{
+        if width == 0 || height == 0{
+            return Err(Error::InvalidSize { width, height });
+        }
 
-        //adapter features & limits
+        //instance
+        let instance = self.create_instance();
 
-        println!("{}",&format!("Adapter Features: {:#?}",adapter.features())[..].green());
-        println!("{}",&format!("Adapter Limits: {:#?}",adapter.features())[..].green());
+        //adapter
+        let adapter = self.request_adapter(&instance,None).await?;
 
+        //device
+        let device = self.request_device(&adapter).await?;
 
+        //not used to configure anything, but lets pipelines query the target format/size like usual
+        let config = wgpu::SurfaceConfiguration{
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format,
+            width,
+            height,
+            present_mode: self.present_mode,
+            desired_maximum_frame_latency: self.frame_latency,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
 
-        //surface present modes,formats, usages
-        println!("{}",&format!("Surface [Present Modes]: {:#?}",surface_capabilities.present_modes)[..].green());
-        println!("{}",&format!("Surface [Formats]: {:#?}",surface_capabilities.formats)[..].green());
-        println!("{}",&format!("Surface [Usages]: {:#?}",surface_capabilities.usages)[..].green());
+        let offscreen = Texture::render_target(&device.0,width,height,format);
+        self.create_context(instance,adapter,device,None,config,Some(offscreen))
+    }
 
+    //Everything build and build_headless share: watching the device, configuring the surface (if there is one),
+    //the context's own state and targets
+    fn create_context<'a>(
+        &self,
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        (device,queue): (wgpu::Device,wgpu::Queue),
+        surface: Option<(wgpu::Surface<'a>,&'a winit::window::Window)>,
+        config: wgpu::SurfaceConfiguration,
+        offscreen: Option<Texture>,
+    ) -> Result<Context<'a>>{
+        let device_lost = Arc::new(AtomicBool::new(false));
+        watch_device_lost(&device,device_lost.clone());
 
+        let (surface,window) = match surface{
+            Some((surface,window)) => {
+                surface.configure(&device,&config);
+                (Some(surface),Some(window))
+            },
+            None => (None,None)
+        };
 
-        Self{
+        let mut context = Context{
             instance,
             surface,
+            window,
+            scale_factor: window.map(|window| window.scale_factor()).unwrap_or(1.0),
             adapter,
             device,
             queue,
-            size,
+            size: winit::dpi::PhysicalSize::new(config.width,config.height),
             config,
-            init: true
+            offscreen,
+            sample_count: 1,
+            msaa_target: None,
+            depth_target: None,
+            init: true,
+            device_lost,
+            builder: self.clone(),
+            screenshot_request: RefCell::new(None),
+            viewport_requests: RefCell::new(vec![]),
+            exit_request: Cell::new(false),
+            time: Time::new(),
+            input: Input::new(),
+            simulated_frame_delta: None,
+        };
+        context.set_sample_count(self.sample_count)?;
+
+        context.capabilities().log(self.log_level);
+
+        Ok(context)
+    }
+
+    fn create_instance(&self) -> wgpu::Instance{
+        wgpu::Instance::new(wgpu::InstanceDescriptor{
+            backends: self.backends,
+            ..Default::default()
+        })
+    }
+
+    async fn request_adapter(&self,instance: &wgpu::Instance,compatible_surface: Option<&wgpu::Surface<'_>>) -> Result<wgpu::Adapter>{
+        //explicitly chosen adapter
+        if let Some(selector) = AdapterSelector::from_env().or(self.adapter.clone()){
+            let adapter = selector.select(instance,self.backends,compatible_surface).ok_or(Error::AdapterNotFound(selector))?;
+            log::info!("Using adapter: {:?}",adapter.get_info());
+            return Ok(adapter);
+        }
+
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions{
+            power_preference: self.power_preference,
+            force_fallback_adapter: self.force_fallback_adapter,
+            compatible_surface,
+        }).await;
+
+        match (adapter,compatible_surface){
+            (Some(adapter),_) => Ok(adapter),
+            //headless, so fallback to a software adapter if there's no real gpu around
+            (None,None) => instance.request_adapter(&wgpu::RequestAdapterOptions{
+                power_preference: self.power_preference,
+                force_fallback_adapter: true,
+                compatible_surface: None,
+            }).await.ok_or(Error::NoAdapter),
+            (None,Some(_)) => Err(Error::NoAdapter)
         }
     }
 
-    pub fn init(&self) -> bool{
-        self.init
+    async fn request_device(&self,adapter: &wgpu::Adapter) -> Result<(wgpu::Device,wgpu::Queue)>{
+        //check requested features & limits up front so we can say what exactly is missing
+        let missing = self.required_features - adapter.features();
+        if !missing.is_empty(){
+            return Err(Error::UnsupportedFeatures(missing));
+        }
+
+        let mut unsupported_limit = None;
+        self.limits.check_limits_with_fail_fn(&adapter.limits(), true, |name,requested,allowed|{
+            unsupported_limit = Some(Error::UnsupportedLimit { name, requested, allowed });
+        });
+        if let Some(e) = unsupported_limit{
+            return Err(e);
+        }
+
+        let features = self.required_features | (self.optional_features & adapter.features());
+
+        adapter.request_device(&wgpu::DeviceDescriptor{
+            label: Some("my device"),
+            required_features: features,
+            required_limits: self.limits.clone(),
+        },None).await.map_err(Error::Device)
+    }
+}
+
+fn watch_device_lost(device: &wgpu::Device,device_lost: Arc<AtomicBool>){
+    device.set_device_lost_callback(move |reason,message|{
+        //dropping/replacing the callback isn't an actual loss (happens when we recreate the device ourselves)
+        if let wgpu::DeviceLostReason::Unknown | wgpu::DeviceLostReason::Destroyed | wgpu::DeviceLostReason::DeviceInvalid = reason{
+            log::error!("Device lost ({reason:?}): {message}");
+            device_lost.store(true,Ordering::SeqCst);
+        }
+    });
+}
+
+//Configuration for a window's surface, uses format if the surface supports it, otherwise prefers srgb, otherwise the first one
+//present_mode has to be supported already
+pub(crate) fn surface_config(capabilities: &wgpu::SurfaceCapabilities,format: Option<wgpu::TextureFormat>,present_mode: wgpu::PresentMode,frame_latency: u32,size: winit::dpi::PhysicalSize<u32>) -> wgpu::SurfaceConfiguration{
+    let format = format.filter(|format| capabilities.formats.contains(format))
+        .or_else(|| capabilities.formats.iter().find(|f| f.is_srgb()).copied())
+        .unwrap_or(capabilities.formats[0]);
+
+    wgpu::SurfaceConfiguration{
+        //allow copying frames back to the cpu (screenshots) when the surface supports it
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | (capabilities.usages & wgpu::TextureUsages::COPY_SRC),
+        format,
+        width: size.width.max(1),
+        height: size.height.max(1),
+        present_mode,
+        desired_maximum_frame_latency: frame_latency,
+        alpha_mode: capabilities.alpha_modes[0],
+        view_formats: vec![],
     }
+}
 
+//Gets the next texture of a surface, see Context::acquire_frame
+pub(crate) fn acquire_surface_frame(surface: &wgpu::Surface,device: &wgpu::Device,config: &wgpu::SurfaceConfiguration) -> Result<Option<wgpu::SurfaceTexture>>{
+    match surface.get_current_texture(){
+        Ok(frame) => Ok(Some(frame)),
+        Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+            surface.configure(device,config);
+            match surface.get_current_texture(){
+                Ok(frame) => Ok(Some(frame)),
+                //still not usable, just try again next frame
+                Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost | wgpu::SurfaceError::Timeout) => Ok(None),
+                Err(e) => Err(e.into())
+            }
+        },
+        Err(wgpu::SurfaceError::Timeout) => Ok(None),
+        Err(e) => Err(e.into())
+    }
+}
+
+//Headless context on any adapter (software ones too), for tests that need a device
+#[cfg(test)]
+pub(crate) fn test_context(width: u32,height: u32,format: wgpu::TextureFormat) -> Context<'static>{
+    pollster::block_on(ContextBuilder::new().with_backends(wgpu::Backends::all()).build_headless(width,height,format))
+        .expect("tests need an adapter")
+}
 
-}
\ No newline at end of file
+//Makes sure the adapter can multisample a format with the given sample count
+pub fn check_sample_count(adapter: &wgpu::Adapter,format: wgpu::TextureFormat,sample_count: u32) -> Result<()>{
+    let supported = adapter.get_texture_format_features(format).flags.sample_count_supported(sample_count);
+    if supported{
+        Ok(())
+    }
+    else{
+        Err(Error::UnsupportedSampleCount { format, sample_count })
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    //clears the offscreen target and reads it back
+    fn clear(ctx: &Context,color: wgpu::Color) -> image::RgbaImage{
+        let offscreen = ctx.offscreen.as_ref().unwrap();
+        let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+            label: Some("my cmd encoder"),
+        });
+        {
+            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor{
+                label: Some("my render pass"),
+                color_attachments: &[Some(ctx.color_attachment(&offscreen.view,wgpu::LoadOp::Clear(color)))],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+        ctx.queue.submit(std::iter::once(encoder.finish()));
+        ctx.capture_texture(&offscreen.texture).unwrap()
+    }
+
+    #[test]
+    fn headless_rejects_empty_targets(){
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        assert!(matches!(pollster::block_on(Context::new_headless(0,8,format)),Err(Error::InvalidSize { width: 0, height: 8 })));
+        assert!(matches!(pollster::block_on(Context::new_headless(8,0,format)),Err(Error::InvalidSize { width: 8, height: 0 })));
+    }
+
+    #[test]
+    fn headless_renders_after_recreating_the_device(){
+        let mut ctx = test_context(8,8,wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(clear(&ctx,wgpu::Color::RED).get_pixel(0,0).0,[255,0,0,255]);
+
+        pollster::block_on(ctx.recreate_device()).unwrap();
+        let image = clear(&ctx,wgpu::Color::GREEN);
+        assert_eq!(image.dimensions(),(8,8));
+        assert_eq!(image.get_pixel(7,7).0,[0,255,0,255]);
+    }
+}
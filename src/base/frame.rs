@@ -0,0 +1,42 @@
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+
+//Texture a frame gets rendered into
+pub enum FrameTexture{
+    //swapchain texture of a window surface
+    Surface(wgpu::SurfaceTexture),
+    //context owned offscreen target (headless)
+    Offscreen(wgpu::Texture),
+}
+
+//So frame.texture can be used just like a wgpu::Texture (ex: frame.texture.create_view(..))
+impl Deref for FrameTexture{
+    type Target = wgpu::Texture;
+
+    fn deref(&self) -> &Self::Target {
+        match self{
+            FrameTexture::Surface(surface_texture) => &surface_texture.texture,
+            FrameTexture::Offscreen(texture) => texture,
+        }
+    }
+}
+
+//A frame handed to render functions
+//present() gives the texture back to the app, which presents it after render returns
+//(so hydra can still read the finished frame, ex: for screenshots)
+pub struct Frame{
+    pub texture: FrameTexture,
+    presented: Rc<RefCell<Option<FrameTexture>>>,
+}
+
+impl Frame{
+    pub(crate) fn new(texture: FrameTexture) -> (Self,Rc<RefCell<Option<FrameTexture>>>){
+        let presented = Rc::new(RefCell::new(None));
+        (Frame{ texture, presented: presented.clone() },presented)
+    }
+
+    pub fn present(self){
+        *self.presented.borrow_mut() = Some(self.texture);
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use winit::event::{ElementState, MouseButton};
+
+use crate::base::app::{App, EventHandler, Frame, Key, Position, Size};
+use crate::base::context::Context;
+use crate::core::ui::Ui;
+use crate::error::Result;
+
+//Alternative to the App callback builder for larger apps: implement the methods you need,
+//everything gets the state mutably, then start it with MyApp::run() (or App::from_hydra_app() to customize the app first)
+pub trait HydraApp: Sized + 'static{
+
+    //If true a Ui gets created and ui() is called every frame instead of render()
+    const UI: bool = false;
+
+    //If set fixed_update() gets called at this rate (see App::fixed_update)
+    const FIXED_TIMESTEP: Option<Duration> = None;
+
+    fn init(app: &App<Self>,ctx: &Context) -> Self;
+
+    fn update(&mut self,_ctx: &Context){}
+
+    //Only called if FIXED_TIMESTEP is set
+    fn fixed_update(&mut self,_ctx: &Context){}
+
+    fn render(&mut self,_ctx: &Context,frame: Frame){
+        frame.present();
+    }
+
+    //Only called if UI is true
+    fn ui(&mut self,_ui: &mut Ui,ctx: &Context,frame: Frame){
+        self.render(ctx,frame);
+    }
+
+    //Every presented frame, before it goes to the screen (see App::on_frame)
+    fn on_frame(&mut self,_ctx: &Context,_texture: &wgpu::Texture){}
+
+    fn resize(&mut self,_ctx: &Context,_width: u32,_height: u32){}
+
+    fn on_key(&mut self,_key: Key,_state: ElementState,_control: &EventHandler){}
+
+    fn on_mouse(&mut self,_button: MouseButton,_state: ElementState,_control: &EventHandler){}
+
+    fn on_cursor_move(&mut self,_position: Position,_size: Size,_control: &EventHandler){}
+
+    fn on_mouse_move(&mut self,_delta: (f32,f32),_control: &EventHandler){}
+
+    fn on_scroll(&mut self,_delta: (f32,f32),_control: &EventHandler){}
+
+    fn on_text(&mut self,_text: &str,_control: &EventHandler){}
+
+    fn on_file_drop(&mut self,_path: PathBuf,_control: &EventHandler){}
+
+    fn on_focus(&mut self,_focused: bool,_control: &EventHandler){}
+
+    fn on_occluded(&mut self,_occluded: bool,_control: &EventHandler){}
+
+    fn on_scale_factor_changed(&mut self,_ctx: &Context,_scale_factor: f64){}
+
+    //Gpu resources (pipelines,buffers,textures...) need to be rebuilt after the device was lost
+    fn on_device_recreated(&mut self,_ctx: &Context){}
+
+    //Called once when the event loop exits
+    fn on_exit(&mut self,_ctx: &Context){}
+
+    fn run() -> Result<()>{
+        App::<Self>::from_hydra_app().run()
+    }
+}
+
+impl<'window,T: HydraApp> App<'window,T>{
+
+    //An App driven by the HydraApp implementation of T, can still be customized (title,context...) before running
+    pub fn from_hydra_app() -> Self{
+        let mut app = App::new(T::init)
+            .update(T::update)
+            .on_frame(T::on_frame)
+            .on_window_resize(T::resize)
+            .on_key(T::on_key)
+            .on_mouse_input(T::on_mouse)
+            .on_cursor_move(T::on_cursor_move)
+            .on_mouse_move(T::on_mouse_move)
+            .on_scroll(T::on_scroll)
+            .on_text(T::on_text)
+            .on_file_drop(T::on_file_drop)
+            .on_focus(T::on_focus)
+            .on_occluded(T::on_occluded)
+            .on_scale_factor_changed(T::on_scale_factor_changed)
+            .on_device_recreated(T::on_device_recreated)
+            .on_exit(T::on_exit);
+
+        if let Some(timestep) = T::FIXED_TIMESTEP{
+            app = app.fixed_update(timestep,T::fixed_update);
+        }
+
+        if T::UI{
+            app.render_with_ui_mut(T::ui)
+        }
+        else{
+            app.render_mut(T::render)
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{ModifiersState, PhysicalKey::Code};
+
+use crate::base::actions::ActionMap;
+use crate::base::app::{Key, Position};
+
+//pixel scroll deltas (touchpads) get converted to lines using this
+const PIXELS_PER_LINE: f32 = 20.0;
+
+//Scroll delta in lines
+fn scroll_lines(delta: &MouseScrollDelta) -> (f32,f32){
+    match delta{
+        MouseScrollDelta::LineDelta(x,y) => (*x,*y),
+        MouseScrollDelta::PixelDelta(position) => (position.x as f32 / PIXELS_PER_LINE,position.y as f32 / PIXELS_PER_LINE),
+    }
+}
+
+//Input the app dispatches to Input and the input callbacks, serializable so it can be recorded and replayed
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub enum InputEvent{
+    Key{key: Key,state: ElementState},
+    Text(String),
+    MouseInput{button: MouseButton,state: ElementState},
+    //physical pixels
+    CursorMoved{x: f64,y: f64},
+    CursorLeft,
+    //raw mouse motion
+    MouseMotion{dx: f32,dy: f32},
+    //lines
+    Scroll{dx: f32,dy: f32},
+    Modifiers(ModifiersState),
+    Focused(bool),
+}
+
+impl InputEvent{
+    //The input events a window event turns into (a key press can also type text)
+    //With ime enabled the typed text only comes from Ime::Commit, so keys don't type it twice
+    pub fn from_window_event(event: &WindowEvent,ime: bool) -> Vec<InputEvent>{
+        match event{
+            WindowEvent::KeyboardInput { event: KeyEvent{ physical_key: Code(key),state,text,..},..} => {
+                let mut events = vec![InputEvent::Key { key: *key, state: *state }];
+                //some platforms fill in text on release too
+                if let (Some(text),ElementState::Pressed,false) = (text,state,ime){
+                    events.push(InputEvent::Text(text.to_string()));
+                }
+                events
+            },
+            WindowEvent::Ime(Ime::Commit(text)) => vec![InputEvent::Text(text.clone())],
+            WindowEvent::MouseInput { state, button,.. } => vec![InputEvent::MouseInput { button: *button, state: *state }],
+            WindowEvent::CursorMoved { position,.. } => vec![InputEvent::CursorMoved { x: position.x, y: position.y }],
+            WindowEvent::CursorLeft { .. } => vec![InputEvent::CursorLeft],
+            WindowEvent::MouseWheel { delta,.. } => {
+                let (dx,dy) = scroll_lines(delta);
+                vec![InputEvent::Scroll { dx, dy }]
+            },
+            WindowEvent::ModifiersChanged(modifiers) => vec![InputEvent::Modifiers(modifiers.state())],
+            WindowEvent::Focused(focused) => vec![InputEvent::Focused(*focused)],
+            _ => vec![]
+        }
+    }
+}
+
+//Keyboard/mouse state kept up to date by the app (ctx.input)
+//"just" pressed/released and the deltas are reset after every frame
+#[derive(Debug,Default)]
+pub struct Input{
+    keys_held: HashSet<Key>,
+    keys_pressed: HashSet<Key>,
+    keys_released: HashSet<Key>,
+
+    buttons_held: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+
+    cursor: Option<Position>,
+    cursor_delta: (f32,f32),
+    mouse_delta: (f32,f32),
+    scroll_delta: (f32,f32),
+    modifiers: ModifiersState,
+
+    //named actions/axes on top of the raw state
+    actions: ActionMap,
+}
+
+impl Input{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    //keys
+
+    pub fn is_key_held(&self,key: Key) -> bool{
+        self.keys_held.contains(&key)
+    }
+
+    //Pressed this frame
+    pub fn is_key_just_pressed(&self,key: Key) -> bool{
+        self.keys_pressed.contains(&key)
+    }
+
+    //Released this frame
+    pub fn is_key_just_released(&self,key: Key) -> bool{
+        self.keys_released.contains(&key)
+    }
+
+    pub fn keys_held(&self) -> impl Iterator<Item = &Key>{
+        self.keys_held.iter()
+    }
+
+    //mouse buttons
+
+    pub fn is_button_held(&self,button: MouseButton) -> bool{
+        self.buttons_held.contains(&button)
+    }
+
+    pub fn is_button_just_pressed(&self,button: MouseButton) -> bool{
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn is_button_just_released(&self,button: MouseButton) -> bool{
+        self.buttons_released.contains(&button)
+    }
+
+    //cursor & mouse
+
+    //Cursor position in physical pixels, none if the cursor isn't over the window
+    pub fn cursor_position(&self) -> Option<Position>{
+        self.cursor
+    }
+
+    //How far the cursor moved over the window this frame (physical pixels)
+    pub fn cursor_delta(&self) -> (f32,f32){
+        self.cursor_delta
+    }
+
+    //Raw mouse motion this frame, keeps working when the cursor is grabbed or at the window border
+    pub fn mouse_delta(&self) -> (f32,f32){
+        self.mouse_delta
+    }
+
+    //Scroll wheel movement this frame in lines
+    pub fn scroll_delta(&self) -> (f32,f32){
+        self.scroll_delta
+    }
+
+    //modifiers
+
+    pub fn modifiers(&self) -> ModifiersState{
+        self.modifiers
+    }
+
+    pub fn shift(&self) -> bool{
+        self.modifiers.shift_key()
+    }
+
+    pub fn ctrl(&self) -> bool{
+        self.modifiers.control_key()
+    }
+
+    pub fn alt(&self) -> bool{
+        self.modifiers.alt_key()
+    }
+
+    pub fn super_key(&self) -> bool{
+        self.modifiers.super_key()
+    }
+
+    //actions
+
+    pub fn actions(&self) -> &ActionMap{
+        &self.actions
+    }
+
+    pub fn actions_mut(&mut self) -> &mut ActionMap{
+        &mut self.actions
+    }
+
+    pub fn set_actions(&mut self,actions: ActionMap){
+        self.actions = actions;
+    }
+
+    //True while any binding of the action is held
+    pub fn is_action_held(&self,action: &str) -> bool{
+        self.actions.is_held(self,action)
+    }
+
+    pub fn is_action_just_pressed(&self,action: &str) -> bool{
+        self.actions.is_just_pressed(self,action)
+    }
+
+    pub fn is_action_just_released(&self,action: &str) -> bool{
+        self.actions.is_just_released(self,action)
+    }
+
+    pub fn axis(&self,axis: &str) -> f32{
+        self.actions.axis(self,axis)
+    }
+
+    //updating
+
+    pub(crate) fn press_key(&mut self,key: Key,state: ElementState){
+        match state{
+            ElementState::Pressed => {
+                //key repeats don't count as new presses
+                if self.keys_held.insert(key){
+                    self.keys_pressed.insert(key);
+                }
+            },
+            ElementState::Released => {
+                if self.keys_held.remove(&key){
+                    self.keys_released.insert(key);
+                }
+            },
+        }
+    }
+
+    pub(crate) fn press_button(&mut self,button: MouseButton,state: ElementState){
+        match state{
+            ElementState::Pressed => {
+                if self.buttons_held.insert(button){
+                    self.buttons_pressed.insert(button);
+                }
+            },
+            ElementState::Released => {
+                if self.buttons_held.remove(&button){
+                    self.buttons_released.insert(button);
+                }
+            },
+        }
+    }
+
+    pub(crate) fn handle_event(&mut self,event: &InputEvent){
+        match event{
+            InputEvent::Key { key, state } => {
+                self.press_key(*key,*state);
+            },
+            InputEvent::MouseInput { button, state } => {
+                self.press_button(*button,*state);
+            },
+            InputEvent::CursorMoved { x, y } => {
+                if let Some(cursor) = self.cursor{
+                    self.cursor_delta.0 += (x - cursor.x) as f32;
+                    self.cursor_delta.1 += (y - cursor.y) as f32;
+                }
+                self.cursor = Some(Position::new(*x,*y));
+            },
+            InputEvent::CursorLeft => {
+                self.cursor = None;
+            },
+            InputEvent::MouseMotion { dx, dy } => {
+                self.mouse_delta.0 += dx;
+                self.mouse_delta.1 += dy;
+            },
+            InputEvent::Scroll { dx, dy } => {
+                self.scroll_delta.0 += dx;
+                self.scroll_delta.1 += dy;
+            },
+            InputEvent::Modifiers(modifiers) => {
+                self.modifiers = *modifiers;
+            },
+            //releases won't arrive while unfocused, so nothing should stay held
+            InputEvent::Focused(false) => {
+                self.release_all();
+            },
+            _ => {}
+        }
+    }
+
+    //Releases every held key and button
+    pub fn release_all(&mut self){
+        self.keys_released.extend(self.keys_held.drain());
+        self.buttons_released.extend(self.buttons_held.drain());
+        self.modifiers = ModifiersState::empty();
+    }
+
+    //Resets the per frame state, called by the app after every frame
+    pub(crate) fn end_frame(&mut self){
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.cursor_delta = (0.0,0.0);
+        self.mouse_delta = (0.0,0.0);
+        self.scroll_delta = (0.0,0.0);
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use winit::keyboard::KeyCode;
+
+    fn key(key: Key,state: ElementState) -> InputEvent{
+        InputEvent::Key { key, state }
+    }
+
+    #[test]
+    fn cursor_delta_accumulates_and_resets_on_leave(){
+        let mut input = Input::new();
+        //no delta for the first position
+        input.handle_event(&InputEvent::CursorMoved { x: 10.0, y: 10.0 });
+        assert_eq!(input.cursor_delta(),(0.0,0.0));
+        input.handle_event(&InputEvent::CursorMoved { x: 15.0, y: 8.0 });
+        input.handle_event(&InputEvent::CursorMoved { x: 20.0, y: 12.0 });
+        assert_eq!(input.cursor_delta(),(10.0,2.0));
+        assert_eq!(input.cursor_position(),Some(Position::new(20.0,12.0)));
+
+        //coming back in somewhere else isn't a movement
+        input.handle_event(&InputEvent::CursorLeft);
+        assert_eq!(input.cursor_position(),None);
+        input.handle_event(&InputEvent::CursorMoved { x: 100.0, y: 100.0 });
+        assert_eq!(input.cursor_delta(),(10.0,2.0));
+    }
+
+    #[test]
+    fn losing_focus_releases_everything(){
+        let mut input = Input::new();
+        input.handle_event(&key(KeyCode::KeyW,ElementState::Pressed));
+        input.handle_event(&InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed });
+        input.handle_event(&InputEvent::Modifiers(ModifiersState::SHIFT));
+        input.end_frame();
+
+        input.handle_event(&InputEvent::Focused(false));
+        assert!(!input.is_key_held(KeyCode::KeyW));
+        assert!(input.is_key_just_released(KeyCode::KeyW));
+        assert!(!input.is_button_held(MouseButton::Left));
+        assert!(input.is_button_just_released(MouseButton::Left));
+        assert!(!input.shift());
+
+        //gaining it back doesn't press anything
+        input.end_frame();
+        input.handle_event(&InputEvent::Focused(true));
+        assert!(!input.is_key_just_pressed(KeyCode::KeyW));
+        assert!(!input.is_key_just_released(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn key_repeat_isnt_a_new_press(){
+        let mut input = Input::new();
+        input.handle_event(&key(KeyCode::Space,ElementState::Pressed));
+        assert!(input.is_key_just_pressed(KeyCode::Space));
+        input.end_frame();
+
+        input.handle_event(&key(KeyCode::Space,ElementState::Pressed));
+        assert!(input.is_key_held(KeyCode::Space));
+        assert!(!input.is_key_just_pressed(KeyCode::Space));
+
+        //releasing a key that isn't held does nothing
+        input.handle_event(&key(KeyCode::Enter,ElementState::Released));
+        assert!(!input.is_key_just_released(KeyCode::Enter));
+    }
+
+    #[test]
+    fn end_frame_clears_per_frame_state(){
+        let mut input = Input::new();
+        input.handle_event(&key(KeyCode::KeyA,ElementState::Pressed));
+        input.handle_event(&InputEvent::MouseInput { button: MouseButton::Right, state: ElementState::Pressed });
+        input.handle_event(&InputEvent::CursorMoved { x: 0.0, y: 0.0 });
+        input.handle_event(&InputEvent::CursorMoved { x: 3.0, y: 4.0 });
+        input.handle_event(&InputEvent::MouseMotion { dx: 1.0, dy: 2.0 });
+        input.handle_event(&InputEvent::MouseMotion { dx: 1.0, dy: 2.0 });
+        input.handle_event(&InputEvent::Scroll { dx: 0.0, dy: 1.0 });
+        input.handle_event(&InputEvent::Scroll { dx: 0.0, dy: 0.5 });
+        assert_eq!(input.mouse_delta(),(2.0,4.0));
+        assert_eq!(input.scroll_delta(),(0.0,1.5));
+
+        input.end_frame();
+        assert!(!input.is_key_just_pressed(KeyCode::KeyA));
+        assert!(!input.is_button_just_pressed(MouseButton::Right));
+        assert_eq!(input.cursor_delta(),(0.0,0.0));
+        assert_eq!(input.mouse_delta(),(0.0,0.0));
+        assert_eq!(input.scroll_delta(),(0.0,0.0));
+        //held state and the cursor stay
+        assert!(input.is_key_held(KeyCode::KeyA));
+        assert!(input.is_button_held(MouseButton::Right));
+        assert_eq!(input.cursor_position(),Some(Position::new(3.0,4.0)));
+    }
+}
@@ -0,0 +1,122 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::base::vertex::VertexLayout;
+use crate::core::mesh::Mesh;
+use crate::error::{Error, Result};
+
+//worker threads are capped at this, loading is mostly waiting on the disk anyway
+const MAX_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+//Runs work (ex: loading meshes/images) on worker threads, every result is sent to the app as a user event
+//and arrives in on_user_event, so the loop never blocks on it
+//Cheap to clone, keep one in the state to start jobs after init
+pub struct Jobs<E: 'static>{
+    //workers are only started by the first job, apps that never use jobs don't get any threads
+    sender: Arc<OnceLock<Sender<Job>>>,
+    proxy: EventLoopProxy<E>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl<E: 'static> Clone for Jobs<E>{
+    fn clone(&self) -> Self {
+        Self{
+            sender: self.sender.clone(),
+            proxy: self.proxy.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<E: Send + 'static> Jobs<E>{
+    //The worker threads start with the first job and stop once every clone is dropped
+    pub fn new(proxy: EventLoopProxy<E>) -> Self{
+        Self{
+            sender: Arc::new(OnceLock::new()),
+            proxy,
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    //Runs job on a worker thread and sends what it returns to on_user_event
+    pub fn spawn(&self,job: impl FnOnce() -> E + Send + 'static){
+        let proxy = self.proxy.clone();
+        let pending = self.pending.clone();
+        pending.fetch_add(1,Ordering::SeqCst);
+
+        let job: Job = Box::new(move ||{
+            //a panicking job shouldn't take the worker down with it
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            pending.fetch_sub(1,Ordering::SeqCst);
+            match result{
+                //fails only if the loop already exited, nobody is waiting for the result then
+                Ok(event) => { let _ = proxy.send_event(event); },
+                Err(_) => log::error!("Job panicked"),
+            }
+        });
+        if self.sender.get_or_init(start_workers).send(job).is_err(){
+            self.pending.fetch_sub(1,Ordering::SeqCst);
+            log::error!("No worker threads left to run the job");
+        }
+    }
+
+    //Loads an obj file, f turns the result into the app's event
+    pub fn load_mesh<V: VertexLayout + Send + 'static>(&self,path: impl Into<PathBuf>,f: impl FnOnce(Result<Mesh<V>>) -> E + Send + 'static){
+        let path = path.into();
+        self.spawn(move || f(Mesh::from_obj(&path.to_string_lossy())));
+    }
+
+    //Decodes an image file, upload it on the main thread with Texture::from_image
+    pub fn load_image(&self,path: impl Into<PathBuf>,f: impl FnOnce(Result<image::RgbaImage>) -> E + Send + 'static){
+        let path = path.into();
+        self.spawn(move || f(image::open(&path).map(|image| image.to_rgba8()).map_err(Error::Texture)));
+    }
+
+    //Jobs that were started but haven't finished yet
+    pub fn pending(&self) -> usize{
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    //For sending events from threads hydra doesn't manage
+    pub fn proxy(&self) -> &EventLoopProxy<E>{
+        &self.proxy
+    }
+}
+
+fn start_workers() -> Sender<Job>{
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(MAX_WORKERS);
+    let (sender,receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for i in 0..workers{
+        let receiver = receiver.clone();
+        let spawned = std::thread::Builder::new()
+            .name(format!("hydra worker {i}"))
+            .spawn(move || work(receiver));
+        if let Err(e) = spawned{
+            log::error!("Failed to start worker thread: {e}");
+        }
+    }
+    sender
+}
+
+fn work(receiver: Arc<Mutex<Receiver<Job>>>){
+    loop{
+        //only hold the lock while waiting for the next job
+        let job = match receiver.lock(){
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return
+        };
+        match job{
+            Ok(job) => job(),
+            //every sender was dropped
+            Err(_) => return
+        }
+    }
+}
@@ -1,5 +1,18 @@
+pub mod actions;
+pub mod adapter;
 pub mod app;
+pub mod capture;
 pub mod context;
+pub mod frame;
+pub mod hydra_app;
+pub mod input;
+pub mod jobs;
 pub mod pipeline;
+pub mod plugin;
+pub mod replay;
+pub mod screen;
 pub mod vertex;
-pub mod texture;
\ No newline at end of file
+pub mod viewport;
+pub mod texture;
+pub mod time;
+pub mod window;
\ No newline at end of file
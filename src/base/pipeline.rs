@@ -3,6 +3,7 @@ use std::num::NonZeroU32;
 use wgpu::{core::id::markers::PipelineLayout, hal::DepthStencilAttachment, Face, FrontFace, ShaderModule, ShaderSource, VertexBufferLayout};
 
 use crate::base::context;
+use crate::error::{Error, Result};
 
 pub struct RenderPipelineBuilder<'a>{
 
@@ -14,6 +15,8 @@ pub struct RenderPipelineBuilder<'a>{
 
     //vertex state structures
     shader_module: Option<wgpu::ShaderModule>,
+    //validation error from compiling the shader module (reported when building)
+    shader_error: Option<String>,
     vertex_entry: Option<&'a str>,
     fragment_entry: Option<&'a str>,
     vertex_buffer_layouts: Vec<VertexBufferLayout<'a>>,
@@ -41,6 +44,7 @@ impl<'a> RenderPipelineBuilder<'a>{
             context: context,
             layout: None,
             shader_module: None,
+            shader_error: None,
             vertex_entry: None,
             fragment_entry: None,
             vertex_buffer_layouts: vec![],
@@ -62,10 +66,13 @@ impl<'a> RenderPipelineBuilder<'a>{
         self.vertex_entry = Some(&vertex_entry);
         self.fragment_entry = Some(&fragment_entry);
 
+        //catch compilation errors instead of letting wgpu panic on them
+        self.context.device.push_error_scope(wgpu::ErrorFilter::Validation);
         self.shader_module = Some(self.context.device.create_shader_module(wgpu::ShaderModuleDescriptor{
             label: Some("my shader module"),
             source: shader_source,
         }));
+        self.shader_error = pollster::block_on(self.context.device.pop_error_scope()).map(|e| e.to_string());
 
         self
     }
@@ -86,23 +93,50 @@ impl<'a> RenderPipelineBuilder<'a>{
         self
     }
 
+    //Sample count for msaa, has to match the attachments the pipeline renders into (ex: Context::sample_count)
+    pub fn with_sample_count(mut self,sample_count: u32) -> Self{
+        self.multisample.count = sample_count;
+        self
+    }
+
     pub fn add_color_target_state(mut self,target: wgpu::ColorTargetState) -> Self{
         self.color_targets.push(Some(target));
         self
     }
 
     pub fn build(self) -> wgpu::RenderPipeline{
+        self.try_build().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    //Like build, but returns shader/pipeline validation errors instead of panicking
+    pub fn try_build(self) -> Result<wgpu::RenderPipeline>{
+
+        if let Some(e) = self.shader_error{
+            return Err(Error::Shader(e));
+        }
+
+        //make sure every attachment format can actually be multisampled this much
+        if self.multisample.count > 1{
+            for target in self.color_targets.iter().flatten(){
+                context::check_sample_count(&self.context.adapter,target.format,self.multisample.count)?;
+            }
+            if let Some(depth_stencil) = &self.depth_stencil{
+                context::check_sample_count(&self.context.adapter,depth_stencil.format,self.multisample.count)?;
+            }
+        }
+
+        let shader_module = self.shader_module.as_ref().ok_or(Error::Pipeline("no shader module".to_string()))?;
 
         let vertex_state = wgpu::VertexState{
-            module: self.shader_module.as_ref().expect("pipeline creation: no shader module"),
-            entry_point: self.vertex_entry.as_ref().expect("pipeline creation: no shader module entry point"),
+            module: shader_module,
+            entry_point: self.vertex_entry.ok_or(Error::Pipeline("no vertex shader entry point".to_string()))?,
             compilation_options: Default::default(),
             buffers: &self.vertex_buffer_layouts[..],
         };
 
         let fragment_state = wgpu::FragmentState{
-            module: self.shader_module.as_ref().expect("pipeline creation: no shader module"),
-            entry_point: self.fragment_entry.as_ref().expect("pipeline creation: no shader module entry point"),
+            module: shader_module,
+            entry_point: self.fragment_entry.ok_or(Error::Pipeline("no fragment shader entry point".to_string()))?,
             compilation_options: Default::default(),
             targets: &self.color_targets[..],
         };
@@ -118,6 +152,11 @@ impl<'a> RenderPipelineBuilder<'a>{
             multiview: self.multiview,
         };
 
-        self.context.device.create_render_pipeline(&descriptor)
+        self.context.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = self.context.device.create_render_pipeline(&descriptor);
+        match pollster::block_on(self.context.device.pop_error_scope()){
+            Some(e) => Err(Error::Pipeline(e.to_string())),
+            None => Ok(pipeline)
+        }
     }
 }
\ No newline at end of file
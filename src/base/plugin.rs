@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use winit::event::WindowEvent;
+
+use crate::base::actions::{self, Binding};
+use crate::base::app::{EventHandler, Key};
+use crate::base::context::Context;
+use crate::base::input::InputEvent;
+
+//Reusable extension of an App (registered with App::add_plugin), for things every project needs
+//Every hook does nothing by default, plugins get called in the order they were added
+//Hooks that get the state only run once the app's init is done
+pub trait Plugin<T,E: 'static = ()>{
+    //Called once the context exists, before the app's init (ex: to add input bindings)
+    //Bindings added here are saved to the actions file along with the app's ones
+    fn init(&mut self,_ctx: &mut Context){}
+
+    //Every frame, before the fixed updates and update
+    fn pre_update(&mut self,_state: &mut T,_ctx: &Context){}
+
+    //Every frame, after update
+    fn post_update(&mut self,_state: &mut T,_ctx: &Context){}
+
+    //Before the app's render
+    fn pre_render(&mut self,_state: &mut T,_ctx: &Context){}
+
+    //After the app presented a frame, before screenshots/recording and before it goes to the screen (ex: to draw an overlay)
+    fn post_render(&mut self,_state: &mut T,_ctx: &Context,_texture: &wgpu::Texture){}
+
+    //Every event of the main window, before the app handles it
+    fn on_window_event(&mut self,_state: &mut T,_ctx: &Context,_event: &WindowEvent,_control: &EventHandler<E>){}
+
+    //Live and replayed input, after ctx.input was updated
+    fn on_input(&mut self,_state: &mut T,_ctx: &Context,_event: &InputEvent,_control: &EventHandler<E>){}
+
+    //After the app's on_exit
+    fn on_exit(&mut self,_state: &mut T,_ctx: &Context){}
+}
+
+
+//Saves the frame as a png into a directory when the screenshot action is pressed (see App::with_screenshot_key)
+pub struct ScreenshotPlugin{
+    key: Key,
+    dir: PathBuf,
+}
+
+impl ScreenshotPlugin{
+    //key gets bound to the screenshot action, unless something already binds it
+    pub fn new(key: Key,dir: impl Into<PathBuf>) -> Self{
+        Self{
+            key,
+            dir: dir.into(),
+        }
+    }
+}
+
+impl<T,E: 'static> Plugin<T,E> for ScreenshotPlugin{
+    fn init(&mut self,ctx: &mut Context){
+        if !ctx.input.actions().is_bound(actions::SCREENSHOT){
+            ctx.input.actions_mut().add_binding(actions::SCREENSHOT,Binding::Key(self.key));
+        }
+    }
+
+    fn pre_update(&mut self,_state: &mut T,ctx: &Context){
+        if ctx.input.is_action_just_pressed(actions::SCREENSHOT){
+            let millis = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis();
+            ctx.request_screenshot(self.dir.join(format!("screenshot_{millis}.png")));
+        }
+    }
+}
+
+
+//Binds a key (ex: Escape) to the quit action, so pressing it exits the app
+pub struct QuitPlugin{
+    key: Key,
+}
+
+impl QuitPlugin{
+    pub fn new(key: Key) -> Self{
+        Self{ key }
+    }
+}
+
+impl<T,E: 'static> Plugin<T,E> for QuitPlugin{
+    fn init(&mut self,ctx: &mut Context){
+        ctx.input.actions_mut().add_binding(actions::QUIT,Binding::Key(self.key));
+    }
+}
+
+
+//Shows the frame rate in the window title (or logs it when headless) every interval
+pub struct FpsPlugin{
+    interval: Duration,
+    last_shown: Duration,
+    title: Option<String>,
+}
+
+impl Default for FpsPlugin{
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+impl FpsPlugin{
+    pub fn new(interval: Duration) -> Self{
+        Self{
+            interval,
+            last_shown: Duration::ZERO,
+            title: None,
+        }
+    }
+}
+
+impl<T,E: 'static> Plugin<T,E> for FpsPlugin{
+    fn init(&mut self,ctx: &mut Context){
+        self.title = ctx.window.map(|window| window.title());
+    }
+
+    fn post_update(&mut self,_state: &mut T,ctx: &Context){
+        //real time, so it keeps going while paused
+        let now = ctx.time.real_elapsed();
+        if now < self.last_shown + self.interval{
+            return;
+        }
+        self.last_shown = now;
+
+        let fps = ctx.time.fps();
+        match (ctx.window,&self.title){
+            (Some(window),Some(title)) => window.set_title(&format!("{title} - {fps:.0} fps")),
+            _ => log::info!("{fps:.0} fps"),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::base::input::InputEvent;
+use crate::error::{Error, Result};
+
+//Something that happened during a recorded run
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub enum RecordedEvent{
+    Input(InputEvent),
+    //window resized to (width,height)
+    Resized(u32,u32),
+}
+
+//Events of a run, tagged with the frame they happened before, plus the time step of every frame
+//Saved as ron
+#[derive(Debug,Clone,Default,PartialEq,Serialize,Deserialize)]
+pub struct InputRecording{
+    //seconds
+    pub frame_deltas: Vec<f64>,
+    pub events: Vec<(u64,RecordedEvent)>,
+}
+
+impl InputRecording{
+    pub fn load(path: impl AsRef<Path>) -> Result<Self>{
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(|e| Error::Replay(format!("can't read {}: {e}",path.display())))?;
+        Self::from_ron(&s)
+    }
+
+    //Parses a recording, checking every frame delta is a valid duration (the file can be edited by hand)
+    pub fn from_ron(s: &str) -> Result<Self>{
+        let recording: Self = ron::from_str(s).map_err(|e| Error::Replay(e.to_string()))?;
+        for (frame,delta) in recording.frame_deltas.iter().enumerate(){
+            if let Err(e) = Duration::try_from_secs_f64(*delta){
+                return Err(Error::Replay(format!("frame {frame} has an invalid time step {delta}: {e}")));
+            }
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self,path: impl AsRef<Path>) -> Result<()>{
+        let path = path.as_ref();
+        let s = ron::ser::to_string_pretty(self,ron::ser::PrettyConfig::default()).map_err(|e| Error::Replay(e.to_string()))?;
+        std::fs::write(path,s).map_err(|e| Error::Replay(format!("can't write {}: {e}",path.display())))
+    }
+
+    pub fn frame_count(&self) -> u64{
+        self.frame_deltas.len() as u64
+    }
+}
+
+//Builds a recording while the app runs
+#[derive(Debug,Default)]
+pub struct InputRecorder{
+    recording: InputRecording,
+}
+
+impl InputRecorder{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    //Index of the frame that is about to run
+    pub fn frame(&self) -> u64{
+        self.recording.frame_count()
+    }
+
+    pub fn push(&mut self,event: RecordedEvent){
+        let frame = self.frame();
+        self.recording.events.push((frame,event));
+    }
+
+    pub fn end_frame(&mut self,delta: Duration){
+        self.recording.frame_deltas.push(delta.as_secs_f64());
+    }
+
+    pub fn recording(&self) -> &InputRecording{
+        &self.recording
+    }
+
+    pub fn finish(self) -> InputRecording{
+        self.recording
+    }
+}
+
+//Plays a recording back frame by frame
+#[derive(Debug)]
+pub struct InputReplay{
+    recording: InputRecording,
+    frame: u64,
+    next_event: usize,
+}
+
+impl InputReplay{
+    pub fn new(recording: InputRecording) -> Self{
+        Self{
+            recording,
+            frame: 0,
+            next_event: 0,
+        }
+    }
+
+    pub fn frame(&self) -> u64{
+        self.frame
+    }
+
+    //True once every recorded frame was played
+    pub fn is_finished(&self) -> bool{
+        self.frame >= self.recording.frame_count()
+    }
+
+    //Events that happened before the next frame, and that frame's time step
+    pub fn next_frame(&mut self) -> (Vec<RecordedEvent>,Option<Duration>){
+        let mut events = vec![];
+        while let Some((frame,event)) = self.recording.events.get(self.next_event){
+            if *frame > self.frame{
+                break;
+            }
+            events.push(event.clone());
+            self.next_event += 1;
+        }
+        //deltas are checked when loading, but recordings can also be built by hand
+        let delta = self.recording.frame_deltas.get(self.frame as usize).and_then(|delta| Duration::try_from_secs_f64(*delta).ok());
+        self.frame += 1;
+        (events,delta)
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use winit::event::{ElementState, MouseButton};
+
+    use super::*;
+    use crate::base::app::Key;
+
+    fn key(key: Key,state: ElementState) -> RecordedEvent{
+        RecordedEvent::Input(InputEvent::Key { key, state })
+    }
+
+    //3 frames: a key press before the first one, nothing before the second, several events before the third
+    fn record() -> InputRecording{
+        let mut recorder = InputRecorder::new();
+        recorder.push(key(Key::KeyW,ElementState::Pressed));
+        recorder.end_frame(Duration::from_millis(16));
+        recorder.end_frame(Duration::from_millis(17));
+        recorder.push(RecordedEvent::Resized(800,600));
+        recorder.push(RecordedEvent::Input(InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed }));
+        recorder.push(key(Key::KeyW,ElementState::Released));
+        recorder.end_frame(Duration::from_millis(15));
+        recorder.finish()
+    }
+
+    #[test]
+    fn replays_events_per_frame(){
+        let recording = record();
+        assert_eq!(recording.frame_count(),3);
+
+        let path = std::env::temp_dir().join(format!("hydra_replay_test_{}.ron",std::process::id()));
+        recording.save(&path).unwrap();
+        let loaded = InputRecording::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded,recording);
+
+        let mut replay = InputReplay::new(loaded);
+        assert_eq!(replay.next_frame(),(vec![key(Key::KeyW,ElementState::Pressed)],Some(Duration::from_millis(16))));
+        assert_eq!(replay.next_frame(),(vec![],Some(Duration::from_millis(17))));
+        assert!(!replay.is_finished());
+        assert_eq!(replay.next_frame(),(vec![
+            RecordedEvent::Resized(800,600),
+            RecordedEvent::Input(InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed }),
+            key(Key::KeyW,ElementState::Released),
+        ],Some(Duration::from_millis(15))));
+        assert!(replay.is_finished());
+        assert_eq!(replay.frame(),3);
+    }
+
+    #[test]
+    fn rejects_invalid_deltas(){
+        for delta in ["-0.5","NaN","inf"]{
+            let s = format!("(frame_deltas: [0.016, {delta}], events: [])");
+            assert!(InputRecording::from_ron(&s).is_err(),"{delta} was accepted");
+        }
+        assert!(InputRecording::from_ron("(frame_deltas: [0.016], events: [])").is_ok());
+    }
+}
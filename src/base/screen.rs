@@ -0,0 +1,402 @@
+use winit::event::{ElementState, MouseButton};
+
+use crate::base::app::{App, EventHandler, Frame, Key};
+use crate::base::context::Context;
+
+//What the stack should do after a screen's hook
+pub enum Transition<S>{
+    None,
+    //pauses the current screen and shows a new one on top of it
+    Push(Box<dyn Screen<S>>),
+    //leaves the current screen and goes back to the one below, the app exits once the stack is empty
+    Pop,
+    //leaves the current screen for a new one
+    Replace(Box<dyn Screen<S>>),
+    //exits the app
+    Quit,
+}
+
+impl<S> Transition<S>{
+    pub fn push(screen: impl Screen<S> + 'static) -> Self{
+        Transition::Push(Box::new(screen))
+    }
+
+    pub fn replace(screen: impl Screen<S> + 'static) -> Self{
+        Transition::Replace(Box::new(screen))
+    }
+}
+
+//One screen of an app (ex: menu,editor,preview), only the top screen of the stack gets updated, rendered and input
+//Screens share the context and data of type S (assets,settings...), anything else they keep themselves
+pub trait Screen<S>{
+    //Called when the screen becomes the top one by a push/replace (or starts the stack)
+    fn on_enter(&mut self,_shared: &mut S,_ctx: &Context){}
+
+    //Called when the screen gets popped/replaced, or the app exits
+    fn on_exit(&mut self,_shared: &mut S,_ctx: &Context){}
+
+    //Called when another screen gets pushed on top of this one
+    fn on_pause(&mut self,_shared: &mut S,_ctx: &Context){}
+
+    //Called when this screen is the top one again after the one above it was popped
+    fn on_resume(&mut self,_shared: &mut S,_ctx: &Context){}
+
+    fn update(&mut self,_shared: &mut S,_ctx: &Context) -> Transition<S>{
+        Transition::None
+    }
+
+    fn render(&mut self,_shared: &mut S,_ctx: &Context,frame: Frame){
+        frame.present();
+    }
+
+    //Every screen gets resized, not just the top one, so paused screens stay usable
+    fn resize(&mut self,_shared: &mut S,_ctx: &Context,_width: u32,_height: u32){}
+
+    //transitions returned by input handlers happen before the next update
+    fn on_key(&mut self,_shared: &mut S,_key: Key,_state: ElementState) -> Transition<S>{
+        Transition::None
+    }
+
+    fn on_mouse(&mut self,_shared: &mut S,_button: MouseButton,_state: ElementState) -> Transition<S>{
+        Transition::None
+    }
+
+    fn on_mouse_move(&mut self,_shared: &mut S,_delta: (f32,f32)) -> Transition<S>{
+        Transition::None
+    }
+
+    fn on_scroll(&mut self,_shared: &mut S,_delta: (f32,f32)) -> Transition<S>{
+        Transition::None
+    }
+
+    fn on_text(&mut self,_shared: &mut S,_text: &str) -> Transition<S>{
+        Transition::None
+    }
+}
+
+//State of an App made of screens (see App::from_screens)
+pub struct ScreenStack<S>{
+    shared: S,
+    screens: Vec<Box<dyn Screen<S>>>,
+    //transitions from input handlers, they need the context
+    pending: Vec<Transition<S>>,
+    started: bool,
+}
+
+impl<S> ScreenStack<S>{
+    pub fn new(shared: S,first: impl Screen<S> + 'static) -> Self{
+        Self{
+            shared,
+            screens: vec![Box::new(first)],
+            pending: vec![],
+            started: false,
+        }
+    }
+
+    pub fn shared(&self) -> &S{
+        &self.shared
+    }
+
+    pub fn shared_mut(&mut self) -> &mut S{
+        &mut self.shared
+    }
+
+    pub fn len(&self) -> usize{
+        self.screens.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.screens.is_empty()
+    }
+
+    //Enters the first screen, done by App::from_screens once the stack was created
+    pub fn start(&mut self,ctx: &Context){
+        if self.started{
+            return;
+        }
+        self.started = true;
+        if let Some(screen) = self.screens.last_mut(){
+            screen.on_enter(&mut self.shared,ctx);
+        }
+    }
+
+    //Runs a transition right away, returns true if the app should exit (quit, or the last screen was popped)
+    pub fn transition(&mut self,transition: Transition<S>,ctx: &Context) -> bool{
+        match transition{
+            Transition::None => false,
+            Transition::Push(mut screen) => {
+                if let Some(top) = self.screens.last_mut(){
+                    top.on_pause(&mut self.shared,ctx);
+                }
+                screen.on_enter(&mut self.shared,ctx);
+                self.screens.push(screen);
+                false
+            },
+            Transition::Pop => {
+                if let Some(mut top) = self.screens.pop(){
+                    top.on_exit(&mut self.shared,ctx);
+                }
+                match self.screens.last_mut(){
+                    Some(top) => {
+                        top.on_resume(&mut self.shared,ctx);
+                        false
+                    },
+                    None => true,
+                }
+            },
+            Transition::Replace(mut screen) => {
+                if let Some(mut top) = self.screens.pop(){
+                    top.on_exit(&mut self.shared,ctx);
+                }
+                screen.on_enter(&mut self.shared,ctx);
+                self.screens.push(screen);
+                false
+            },
+            Transition::Quit => true,
+        }
+    }
+
+    //Runs the transition before the next update (for places without the context, like input handlers)
+    pub fn queue(&mut self,transition: Transition<S>){
+        if !matches!(transition,Transition::None){
+            self.pending.push(transition);
+        }
+    }
+
+    //Runs the queued transitions then updates the top screen, returns true if the app should exit
+    pub fn update(&mut self,ctx: &Context) -> bool{
+        let mut exit = false;
+        for transition in std::mem::take(&mut self.pending){
+            exit |= self.transition(transition,ctx);
+        }
+        let transition = match self.screens.last_mut(){
+            Some(top) => top.update(&mut self.shared,ctx),
+            None => return exit
+        };
+        exit | self.transition(transition,ctx)
+    }
+
+    pub fn render(&mut self,ctx: &Context,frame: Frame){
+        //nothing to show once the last screen is gone, the app is about to exit
+        if let Some(top) = self.screens.last_mut(){
+            top.render(&mut self.shared,ctx,frame);
+        }
+    }
+
+    pub fn resize(&mut self,ctx: &Context,width: u32,height: u32){
+        for screen in self.screens.iter_mut(){
+            screen.resize(&mut self.shared,ctx,width,height);
+        }
+    }
+
+    //Gives an input event to the top screen, queueing the transition it returns
+    pub fn input(&mut self,f: impl FnOnce(&mut dyn Screen<S>,&mut S) -> Transition<S>){
+        if let Some(top) = self.screens.last_mut(){
+            let transition = f(top.as_mut(),&mut self.shared);
+            self.queue(transition);
+        }
+    }
+
+    //Exits every screen, top one first
+    pub fn exit(&mut self,ctx: &Context){
+        while let Some(mut top) = self.screens.pop(){
+            top.on_exit(&mut self.shared,ctx);
+        }
+    }
+}
+
+impl<'window,S: 'static> App<'window,ScreenStack<S>>{
+
+    //An App whose state is a stack of screens, init creates it (and the shared data) once the context exists
+    //Can still be customized (title,context,plugins...) before running
+    pub fn from_screens(mut init: impl FnMut(&Context) -> ScreenStack<S> + 'static) -> Self{
+        App::new(move |_app,ctx|{
+            let mut stack = init(ctx);
+            stack.start(ctx);
+            stack
+        })
+        .update(|stack,ctx|{
+            if stack.update(ctx){
+                ctx.request_exit();
+            }
+        })
+        .render_mut(|stack,ctx,frame| stack.render(ctx,frame))
+        .on_window_resize(|stack,ctx,width,height| stack.resize(ctx,width,height))
+        .on_key(|stack,key,state,_: &EventHandler| stack.input(|screen,shared| screen.on_key(shared,key,state)))
+        .on_mouse_input(|stack,button,state,_: &EventHandler| stack.input(|screen,shared| screen.on_mouse(shared,button,state)))
+        .on_mouse_move(|stack,delta,_: &EventHandler| stack.input(|screen,shared| screen.on_mouse_move(shared,delta)))
+        .on_scroll(|stack,delta,_: &EventHandler| stack.input(|screen,shared| screen.on_scroll(shared,delta)))
+        .on_text(|stack,text,_: &EventHandler| stack.input(|screen,shared| screen.on_text(shared,text)))
+        .on_exit(|stack,ctx| stack.exit(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::base::context::{test_context, ContextBuilder};
+
+    //screens write every hook call into the shared log
+    type Log = Vec<String>;
+
+    struct Mock(&'static str);
+
+    impl Screen<Log> for Mock{
+        fn on_enter(&mut self,log: &mut Log,_ctx: &Context){
+            log.push(format!("{} enter",self.0));
+        }
+
+        fn on_exit(&mut self,log: &mut Log,_ctx: &Context){
+            log.push(format!("{} exit",self.0));
+        }
+
+        fn on_pause(&mut self,log: &mut Log,_ctx: &Context){
+            log.push(format!("{} pause",self.0));
+        }
+
+        fn on_resume(&mut self,log: &mut Log,_ctx: &Context){
+            log.push(format!("{} resume",self.0));
+        }
+
+        fn update(&mut self,log: &mut Log,_ctx: &Context) -> Transition<Log>{
+            log.push(format!("{} update",self.0));
+            Transition::None
+        }
+
+        fn on_text(&mut self,_log: &mut Log,text: &str) -> Transition<Log>{
+            match text{
+                "push" => Transition::push(Mock("c")),
+                "quit" => Transition::Quit,
+                _ => Transition::None
+            }
+        }
+    }
+
+    //the hooks don't render anything, but still get a context
+    fn context() -> Context<'static>{
+        test_context(4,4,wgpu::TextureFormat::Rgba8UnormSrgb)
+    }
+
+    fn started(ctx: &Context) -> ScreenStack<Log>{
+        let mut stack = ScreenStack::new(vec![],Mock("a"));
+        stack.start(ctx);
+        stack.start(ctx);
+        stack
+    }
+
+    fn take_log(stack: &mut ScreenStack<Log>) -> Log{
+        std::mem::take(stack.shared_mut())
+    }
+
+    #[test]
+    fn push_pauses_and_pop_resumes(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        assert_eq!(take_log(&mut stack),["a enter"]);
+
+        assert!(!stack.transition(Transition::push(Mock("b")),&ctx));
+        assert_eq!(take_log(&mut stack),["a pause","b enter"]);
+        assert_eq!(stack.len(),2);
+
+        assert!(!stack.transition(Transition::Pop,&ctx));
+        assert_eq!(take_log(&mut stack),["b exit","a resume"]);
+        assert_eq!(stack.len(),1);
+    }
+
+    #[test]
+    fn replace_exits_before_entering(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        take_log(&mut stack);
+
+        assert!(!stack.transition(Transition::replace(Mock("b")),&ctx));
+        assert_eq!(take_log(&mut stack),["a exit","b enter"]);
+        assert_eq!(stack.len(),1);
+    }
+
+    #[test]
+    fn popping_the_last_screen_requests_exit(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        take_log(&mut stack);
+
+        assert!(stack.transition(Transition::Pop,&ctx));
+        assert_eq!(take_log(&mut stack),["a exit"]);
+        assert!(stack.is_empty());
+
+        //nothing left to update
+        assert!(!stack.update(&ctx));
+        assert!(take_log(&mut stack).is_empty());
+    }
+
+    #[test]
+    fn quit_keeps_the_stack(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        take_log(&mut stack);
+
+        assert!(stack.transition(Transition::Quit,&ctx));
+        assert!(take_log(&mut stack).is_empty());
+        assert_eq!(stack.len(),1);
+    }
+
+    #[test]
+    fn input_transitions_run_before_update(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        take_log(&mut stack);
+
+        stack.input(|screen,log| screen.on_text(log,"push"));
+        stack.input(|screen,log| screen.on_text(log,"nothing"));
+        assert!(take_log(&mut stack).is_empty());
+
+        assert!(!stack.update(&ctx));
+        assert_eq!(take_log(&mut stack),["a pause","c enter","c update"]);
+    }
+
+    #[test]
+    fn queued_quit_exits_from_update(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        stack.input(|screen,log| screen.on_text(log,"quit"));
+        assert!(stack.update(&ctx));
+        //exiting is up to the app, the stack itself doesn't touch the context
+        assert!(!ctx.is_exit_requested());
+    }
+
+    //quits on its second update
+    struct QuitSoon(Rc<Cell<u32>>);
+
+    impl Screen<()> for QuitSoon{
+        fn update(&mut self,_shared: &mut (),_ctx: &Context) -> Transition<()>{
+            self.0.set(self.0.get() + 1);
+            if self.0.get() == 2 { Transition::Quit } else { Transition::None }
+        }
+    }
+
+    #[test]
+    fn from_screens_stops_the_app_on_quit(){
+        let updates = Rc::new(Cell::new(0));
+        let counter = updates.clone();
+        App::from_screens(move |_| ScreenStack::new((),QuitSoon(counter.clone())))
+            .with_context(ContextBuilder::new().with_backends(wgpu::Backends::all()))
+            .run_headless(8,8,10)
+            .unwrap();
+        assert_eq!(updates.get(),2);
+    }
+
+    #[test]
+    fn exit_goes_top_first(){
+        let ctx = context();
+        let mut stack = started(&ctx);
+        stack.transition(Transition::push(Mock("b")),&ctx);
+        take_log(&mut stack);
+
+        stack.exit(&ctx);
+        assert_eq!(take_log(&mut stack),["b exit","a exit"]);
+        assert!(stack.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use image::GenericImageView;
 use wgpu::ImageCopyTexture;
 
+use crate::error::Result;
+
 //Contains a texture,a texture view, a sampler, and a bind group for that texture
 pub struct Texture{
     pub texture: wgpu::Texture,
@@ -8,11 +9,15 @@ pub struct Texture{
 }
 
 impl Texture{
-    pub fn from_bytes(context: &crate::base::context::Context, bytes: &[u8]) -> Result<Self,image::ImageError>{
+    pub fn from_bytes(context: &crate::base::context::Context, bytes: &[u8]) -> Result<Self>{
         let image = image::load_from_memory(bytes)?;
         //convert it
-        let image_rgba = image.to_rgba8();
-        let image_size = image.dimensions();
+        Ok(Self::from_image(context,&image.to_rgba8()))
+    }
+
+    //Uploads an already decoded image (ex: one loaded on another thread with Jobs::load_image)
+    pub fn from_image(context: &crate::base::context::Context,image_rgba: &image::RgbaImage) -> Self{
+        let image_size = image_rgba.dimensions();
 
         //texture
         let texture_size = wgpu::Extent3d{
@@ -43,7 +48,7 @@ impl Texture{
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            &image_rgba,
+            image_rgba,
             wgpu::ImageDataLayout {
                 offset: 0,
                 bytes_per_row: Some(4*image_size.0),
@@ -56,10 +61,61 @@ impl Texture{
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         
 
-        Ok(Texture{
+        Texture{
             texture,
             view
-        })
+        }
 
     }
-}
\ No newline at end of file
+
+    //Creates an empty texture that can be rendered into (ex: an offscreen color target)
+    pub fn render_target(device: &wgpu::Device,width: u32,height: u32,format: wgpu::TextureFormat) -> Self{
+        let texture = device.create_texture(&wgpu::TextureDescriptor{
+            label: Some("my render target"),
+            size: wgpu::Extent3d{
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            //output of a pipeline, can also be sampled or copied back to the cpu
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Texture{
+            texture,
+            view
+        }
+    }
+
+    //Creates a texture that's only used as a render attachment (ex: msaa color target, depth target)
+    pub fn attachment(device: &wgpu::Device,width: u32,height: u32,format: wgpu::TextureFormat,sample_count: u32) -> Self{
+        let texture = device.create_texture(&wgpu::TextureDescriptor{
+            label: Some("my attachment"),
+            size: wgpu::Extent3d{
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Texture{
+            texture,
+            view
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+//how much of the previous average is kept every frame when smoothing the fps
+const FPS_SMOOTHING: f32 = 0.9;
+
+//Frame timing, updated by the app once per frame before update() is called (ctx.time)
+//Pause/step/time scale only affect the scaled times (delta,elapsed,fixed steps), not real_delta
+pub struct Time{
+    start: Instant,
+    last_frame: Option<Instant>,
+    delta: Duration,
+    real_delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+    fps: f32,
+
+    //fixed timestep
+    fixed_delta: Option<Duration>,
+    accumulator: Duration,
+    //fixed steps per frame are capped, so a slow frame can't snowball into even slower ones
+    max_fixed_steps: u32,
+    alpha: f32,
+
+    //controls, cells so they can be used from update/render which only get &Context
+    paused: Cell<bool>,
+    step_requested: Cell<bool>,
+    time_scale: Cell<f32>,
+}
+
+impl Default for Time{
+    fn default() -> Self {
+        Self{
+            start: Instant::now(),
+            last_frame: None,
+            delta: Duration::ZERO,
+            real_delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame: 0,
+            fps: 0.0,
+            fixed_delta: None,
+            accumulator: Duration::ZERO,
+            max_fixed_steps: 8,
+            alpha: 0.0,
+            paused: Cell::new(false),
+            step_requested: Cell::new(false),
+            time_scale: Cell::new(1.0),
+        }
+    }
+}
+
+impl Time{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    //Scaled time since the last frame (zero while paused)
+    pub fn delta(&self) -> Duration{
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32{
+        self.delta.as_secs_f32()
+    }
+
+    //Unscaled time since the last frame, keeps running while paused
+    pub fn real_delta(&self) -> Duration{
+        self.real_delta
+    }
+
+    //Scaled time since the app started
+    pub fn elapsed(&self) -> Duration{
+        self.elapsed
+    }
+
+    pub fn elapsed_secs(&self) -> f32{
+        self.elapsed.as_secs_f32()
+    }
+
+    //Wall clock time since the app started
+    pub fn real_elapsed(&self) -> Duration{
+        self.start.elapsed()
+    }
+
+    //Index of the current frame, starting at 0
+    pub fn frame(&self) -> u64{
+        self.frame
+    }
+
+    //Frames per second, smoothed over the last frames
+    pub fn fps(&self) -> f32{
+        self.fps
+    }
+
+    //Time step of fixed updates (none if fixed updates aren't used)
+    pub fn fixed_delta(&self) -> Option<Duration>{
+        self.fixed_delta
+    }
+
+    pub fn set_fixed_delta(&mut self,fixed_delta: Option<Duration>){
+        self.fixed_delta = fixed_delta.filter(|dt| !dt.is_zero());
+        self.accumulator = Duration::ZERO;
+    }
+
+    pub fn set_max_fixed_steps(&mut self,max_fixed_steps: u32){
+        self.max_fixed_steps = max_fixed_steps.max(1);
+    }
+
+    //How far the leftover time is into the next fixed step [0,1], used to interpolate between the last two simulation states
+    pub fn alpha(&self) -> f32{
+        self.alpha
+    }
+
+    pub fn is_paused(&self) -> bool{
+        self.paused.get()
+    }
+
+    pub fn set_paused(&self,paused: bool){
+        self.paused.set(paused);
+    }
+
+    pub fn pause(&self){
+        self.set_paused(true);
+    }
+
+    pub fn resume(&self){
+        self.set_paused(false);
+    }
+
+    pub fn toggle_pause(&self){
+        self.set_paused(!self.is_paused());
+    }
+
+    //While paused, advances the next frame by a single step (one fixed step, or the real delta without fixed updates)
+    pub fn step(&self){
+        self.step_requested.set(true);
+    }
+
+    pub fn time_scale(&self) -> f32{
+        self.time_scale.get()
+    }
+
+    //Multiplies every scaled time, ex: 0.5 for slow motion (negative scales count as 0, nan/infinite ones are ignored)
+    pub fn set_time_scale(&self,time_scale: f32){
+        if !time_scale.is_finite(){
+            log::warn!("Ignoring time scale {time_scale}");
+            return;
+        }
+        self.time_scale.set(time_scale.max(0.0));
+    }
+
+    //Measures the next frame's delta from now instead of from the last frame
+    //(ex: after the window was hidden, so the hidden period doesn't show up as one huge frame)
+    pub(crate) fn reset_frame_clock(&mut self){
+        if self.last_frame.is_some(){
+            self.last_frame = Some(Instant::now());
+        }
+    }
+
+    //Advances to the next frame, returns how many fixed updates should run this frame
+    //simulated_delta replaces the measured frame time (ex: while recording)
+    pub(crate) fn tick(&mut self,simulated_delta: Option<Duration>) -> u32{
+        let now = Instant::now();
+        let real_delta = match (simulated_delta,self.last_frame){
+            (Some(delta),_) => delta,
+            (None,Some(last_frame)) => now - last_frame,
+            //first frame
+            (None,None) => Duration::ZERO,
+        };
+        if self.last_frame.is_some(){
+            self.frame += 1;
+        }
+        self.last_frame = Some(now);
+        self.real_delta = real_delta;
+
+        if !real_delta.is_zero(){
+            let fps = 1.0 / real_delta.as_secs_f32();
+            self.fps = if self.fps == 0.0 { fps } else { self.fps * FPS_SMOOTHING + fps * (1.0 - FPS_SMOOTHING) };
+        }
+
+        let stepping = self.step_requested.replace(false);
+        self.delta = match (self.is_paused(),stepping){
+            //mul_f32 isn't exact, so unscaled time is left alone (keeps recorded runs reproducible)
+            (false,_) if self.time_scale() == 1.0 => real_delta,
+            (false,_) => real_delta.mul_f32(self.time_scale()),
+            (true,true) => self.fixed_delta.unwrap_or(real_delta),
+            (true,false) => Duration::ZERO,
+        };
+        self.elapsed += self.delta;
+
+        let fixed_delta = match self.fixed_delta{
+            Some(fixed_delta) => fixed_delta,
+            None => return 0
+        };
+
+        self.accumulator += self.delta;
+        let mut steps = 0;
+        while self.accumulator >= fixed_delta && steps < self.max_fixed_steps{
+            self.accumulator -= fixed_delta;
+            steps += 1;
+        }
+        //drop whatever couldn't be simulated instead of carrying it into the next frames, only the partial step is kept
+        if steps == self.max_fixed_steps{
+            let remainder = self.accumulator.as_nanos() % fixed_delta.as_nanos();
+            self.accumulator = Duration::from_nanos(remainder as u64);
+        }
+        self.alpha = (self.accumulator.as_secs_f64() / fixed_delta.as_secs_f64()).min(1.0) as f32;
+
+        steps
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn fixed_time(fixed_delta: Duration) -> Time{
+        let mut time = Time::new();
+        time.set_fixed_delta(Some(fixed_delta));
+        time
+    }
+
+    #[test]
+    fn accumulates_fixed_steps(){
+        let mut time = fixed_time(10 * MS);
+        assert_eq!(time.tick(Some(25 * MS)),2);
+        assert!((time.alpha() - 0.5).abs() < 1e-6);
+        assert_eq!(time.tick(Some(5 * MS)),1);
+        assert_eq!(time.alpha(),0.0);
+        assert_eq!(time.tick(Some(3 * MS)),0);
+        assert!((time.alpha() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn no_fixed_steps_without_fixed_delta(){
+        let mut time = Time::new();
+        assert_eq!(time.tick(Some(100 * MS)),0);
+        assert_eq!(time.delta(),100 * MS);
+        assert_eq!(time.elapsed(),100 * MS);
+    }
+
+    #[test]
+    fn clamps_steps_and_drops_the_rest(){
+        let mut time = fixed_time(10 * MS);
+        time.set_max_fixed_steps(2);
+        assert_eq!(time.tick(Some(105 * MS)),2);
+        //only the partial step is left
+        assert!((time.alpha() - 0.5).abs() < 1e-6);
+        //no catch up steps afterwards
+        assert_eq!(time.tick(Some(Duration::ZERO)),0);
+        assert_eq!(time.tick(Some(4 * MS)),0);
+        assert_eq!(time.tick(Some(MS)),1);
+    }
+
+    #[test]
+    fn pause_and_step(){
+        let mut time = fixed_time(10 * MS);
+        time.pause();
+        assert_eq!(time.tick(Some(16 * MS)),0);
+        assert_eq!(time.delta(),Duration::ZERO);
+        assert_eq!(time.real_delta(),16 * MS);
+
+        time.step();
+        assert_eq!(time.tick(Some(16 * MS)),1);
+        assert_eq!(time.delta(),10 * MS);
+        //a step only lasts one frame
+        assert_eq!(time.tick(Some(16 * MS)),0);
+
+        time.resume();
+        assert_eq!(time.tick(Some(16 * MS)),1);
+        assert_eq!(time.elapsed(),26 * MS);
+    }
+
+    #[test]
+    fn reset_frame_clock_skips_the_gap(){
+        let mut time = Time::new();
+        time.tick(None);
+        std::thread::sleep(50 * MS);
+        time.reset_frame_clock();
+        time.tick(None);
+        assert!(time.real_delta() < 50 * MS);
+        assert!(time.delta() < 50 * MS);
+        assert_eq!(time.frame(),1);
+    }
+
+    #[test]
+    fn time_scale(){
+        let mut time = Time::new();
+        time.set_time_scale(0.5);
+        time.tick(Some(20 * MS));
+        assert_eq!(time.delta(),10 * MS);
+        assert_eq!(time.real_delta(),20 * MS);
+
+        time.set_time_scale(f32::NAN);
+        time.set_time_scale(f32::INFINITY);
+        assert_eq!(time.time_scale(),0.5);
+        time.set_time_scale(-1.0);
+        assert_eq!(time.time_scale(),0.0);
+        time.tick(Some(20 * MS));
+        assert_eq!(time.delta(),Duration::ZERO);
+    }
+}
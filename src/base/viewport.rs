@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use winit::window::{Window, WindowId};
+
+use crate::base::context::{self, Context};
+use crate::error::{Error, Result};
+
+//An extra window of the app, with its own surface but sharing the context's device and queue
+//(so pipelines, buffers and textures can be used in every window)
+pub struct Viewport{
+    pub label: String,
+    //declared before the window so it gets dropped first
+    pub surface: wgpu::Surface<'static>,
+    pub window: Arc<Window>,
+    pub config: wgpu::SurfaceConfiguration,
+    pub size: winit::dpi::PhysicalSize<u32>,
+}
+
+impl Viewport{
+    //Creates a surface for the window, using the main surface's format and present mode when possible
+    //so pipelines built for the main window work here too
+    pub fn new(ctx: &Context,label: &str,window: Arc<Window>) -> Result<Self>{
+        let surface = ctx.instance.create_surface(window.clone()).map_err(Error::CreateSurface)?;
+        if !ctx.adapter.is_surface_supported(&surface){
+            return Err(Error::IncompatibleSurface(label.to_string()));
+        }
+
+        let capabilities = surface.get_capabilities(&ctx.adapter);
+        let present_mode = if capabilities.present_modes.contains(&ctx.config.present_mode){
+            ctx.config.present_mode
+        }
+        else{
+            wgpu::PresentMode::Fifo
+        };
+
+        let size = window.inner_size();
+        let config = context::surface_config(&capabilities,Some(ctx.config.format),present_mode,ctx.config.desired_maximum_frame_latency,size);
+        surface.configure(&ctx.device,&config);
+
+        Ok(Self{
+            label: label.to_string(),
+            surface,
+            window,
+            config,
+            size,
+        })
+    }
+
+    pub fn id(&self) -> WindowId{
+        self.window.id()
+    }
+
+    pub fn resize(&mut self,device: &wgpu::Device,size: winit::dpi::PhysicalSize<u32>){
+        if size.width == 0 || size.height == 0{
+            return;
+        }
+        self.size = size;
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.surface.configure(device,&self.config);
+    }
+
+    //Configures the surface again (ex: after the device got recreated)
+    pub fn reconfigure(&self,device: &wgpu::Device){
+        self.surface.configure(device,&self.config);
+    }
+
+    //Same as Context::acquire_frame, for this window's surface
+    pub fn acquire_frame(&self,device: &wgpu::Device) -> Result<Option<wgpu::SurfaceTexture>>{
+        context::acquire_surface_frame(&self.surface,device,&self.config)
+    }
+}
+
+//Opening/closing viewports from inside callbacks, handled by the app after the current event
+#[derive(Debug,Clone,PartialEq)]
+pub(crate) enum ViewportRequest{
+    Open(String),
+    Close(String),
+}
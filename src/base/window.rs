@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use winit::dpi::PhysicalSize;
+use winit::window::{CursorGrabMode, Fullscreen, Icon, Window, WindowBuilder};
+
+use crate::error::{Error, Result};
+
+//How a window covers the screen
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum FullscreenMode{
+    //borderless window the size of the current monitor
+    Borderless,
+    //changes the monitor's video mode (uses the current monitor's best mode)
+    Exclusive,
+}
+
+//Settings for the window created by App::run
+#[derive(Debug,Clone)]
+pub struct WindowConfig{
+    pub inner_size: Option<(u32,u32)>,
+    pub min_size: Option<(u32,u32)>,
+    pub max_size: Option<(u32,u32)>,
+    pub resizable: bool,
+    pub fullscreen: Option<FullscreenMode>,
+    pub decorations: bool,
+    pub maximized: bool,
+    //image file loaded through the image crate
+    pub icon: Option<PathBuf>,
+    pub cursor_grab: bool,
+}
+
+impl Default for WindowConfig{
+    fn default() -> Self {
+        Self{
+            inner_size: None,
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            fullscreen: None,
+            decorations: true,
+            maximized: false,
+            icon: None,
+            cursor_grab: false,
+        }
+    }
+}
+
+impl WindowConfig{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    //Size of the drawable area in physical pixels
+    pub fn with_size(mut self,width: u32,height: u32) -> Self{
+        self.inner_size = Some((width,height));
+        self
+    }
+
+    pub fn with_min_size(mut self,width: u32,height: u32) -> Self{
+        self.min_size = Some((width,height));
+        self
+    }
+
+    pub fn with_max_size(mut self,width: u32,height: u32) -> Self{
+        self.max_size = Some((width,height));
+        self
+    }
+
+    pub fn with_resizable(mut self,resizable: bool) -> Self{
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn with_fullscreen(mut self,fullscreen: Option<FullscreenMode>) -> Self{
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn with_decorations(mut self,decorations: bool) -> Self{
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn with_maximized(mut self,maximized: bool) -> Self{
+        self.maximized = maximized;
+        self
+    }
+
+    pub fn with_icon(mut self,path: impl Into<PathBuf>) -> Self{
+        self.icon = Some(path.into());
+        self
+    }
+
+    //Grabs and hides the cursor once the window is created (see grab_cursor)
+    pub fn with_cursor_grab(mut self,cursor_grab: bool) -> Self{
+        self.cursor_grab = cursor_grab;
+        self
+    }
+
+    //Applies everything except fullscreen and the cursor grab, which need the window/monitor (see apply)
+    pub fn builder(&self,title: &str) -> Result<WindowBuilder>{
+        let mut builder = WindowBuilder::new()
+            .with_title(title)
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_maximized(self.maximized);
+
+        if let Some((width,height)) = self.inner_size{
+            builder = builder.with_inner_size(PhysicalSize::new(width,height));
+        }
+        if let Some((width,height)) = self.min_size{
+            builder = builder.with_min_inner_size(PhysicalSize::new(width,height));
+        }
+        if let Some((width,height)) = self.max_size{
+            builder = builder.with_max_inner_size(PhysicalSize::new(width,height));
+        }
+        if let Some(path) = &self.icon{
+            builder = builder.with_window_icon(Some(load_icon(path)?));
+        }
+
+        Ok(builder)
+    }
+
+    //Applies the settings that need an existing window
+    pub fn apply(&self,window: &Window) -> Result<()>{
+        if self.fullscreen.is_some(){
+            set_fullscreen(window,self.fullscreen);
+        }
+        if self.cursor_grab{
+            grab_cursor(window,true)?;
+        }
+        Ok(())
+    }
+}
+
+//Loads an image file as a window icon
+pub fn load_icon(path: &std::path::Path) -> Result<Icon>{
+    let image = image::open(path).map_err(Error::Texture)?.into_rgba8();
+    let (width,height) = image.dimensions();
+    Icon::from_rgba(image.into_raw(),width,height).map_err(Error::Icon)
+}
+
+pub fn set_fullscreen(window: &Window,mode: Option<FullscreenMode>){
+    let fullscreen = match mode{
+        Some(FullscreenMode::Borderless) => Some(Fullscreen::Borderless(None)),
+        Some(FullscreenMode::Exclusive) => {
+            //highest resolution, then highest refresh rate of the current monitor
+            let video_mode = window.current_monitor().and_then(|monitor|{
+                monitor.video_modes().max_by_key(|mode| (mode.size().width * mode.size().height,mode.refresh_rate_millihertz()))
+            });
+            match video_mode{
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                None => {
+                    log::warn!("No video mode for exclusive fullscreen, using borderless instead");
+                    Some(Fullscreen::Borderless(None))
+                }
+            }
+        },
+        None => None
+    };
+    window.set_fullscreen(fullscreen);
+}
+
+pub fn is_fullscreen(window: &Window) -> bool{
+    window.fullscreen().is_some()
+}
+
+//Switches between windowed and the given fullscreen mode
+pub fn toggle_fullscreen(window: &Window,mode: FullscreenMode){
+    if is_fullscreen(window){
+        set_fullscreen(window,None);
+    }
+    else{
+        set_fullscreen(window,Some(mode));
+    }
+}
+
+//Locks (or confines, where locking isn't supported) and hides the cursor, raw mouse motion (Input::mouse_delta,on_mouse_move) keeps working
+pub fn grab_cursor(window: &Window,grab: bool) -> Result<()>{
+    if grab{
+        window.set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+            .map_err(Error::CursorGrab)?;
+    }
+    else{
+        window.set_cursor_grab(CursorGrabMode::None).map_err(Error::CursorGrab)?;
+    }
+    window.set_cursor_visible(!grab);
+    Ok(())
+}
@@ -2,6 +2,14 @@ use nalgebra_glm::{half_pi, RealNumber};
 use winit::event::{ElementState, MouseButton};
 
 use crate::base::app::{Key, Position, Size};
+use crate::base::actions;
+use crate::base::context::Context;
+use crate::base::input::Input;
+use crate::base::plugin::Plugin;
+use crate::base::time::Time;
+
+//frame rate the per frame controller speeds are tuned for
+const REFERENCE_FPS: f32 = 60.0;
 
 pub struct PerspectiveParams{
     pub aspect: f32,
@@ -97,6 +105,8 @@ pub struct FlyCameraController{
     back_pressed: bool,
     mouse_moved: bool,
     mouse_pressed: bool,
+    //rotate on every mouse move instead of only while dragging (use with a grabbed cursor)
+    mouse_look: bool,
 }
 
 impl Default for FlyCameraController{
@@ -111,7 +121,8 @@ impl Default for FlyCameraController{
             front_pressed: false,
             back_pressed: false,
             mouse_moved: false,
-            mouse_pressed: false
+            mouse_pressed: false,
+            mouse_look: false,
         }
     }
 }
@@ -125,6 +136,12 @@ impl FlyCameraController{
         }
     }
 
+    //Rotates without holding the left mouse button, meant for windows with a grabbed cursor (Context::grab_cursor)
+    pub fn with_mouse_look(mut self,mouse_look: bool) -> Self{
+        self.mouse_look = mouse_look;
+        self
+    }
+
     pub fn on_key_fn(&mut self,key: Key,key_state: ElementState){
         match key{
             winit::keyboard::KeyCode::KeyW => {
@@ -156,7 +173,7 @@ impl FlyCameraController{
     }
 
     pub fn on_mouse_move_fn(&mut self,delta: (f32,f32)){  
-        if self.mouse_pressed{
+        if self.mouse_pressed || self.mouse_look{
             self.theta += delta.0 * self.rotate_speed;
         }
     }
@@ -173,26 +190,48 @@ impl FlyCameraController{
         }
     }
 
+    //Moves translate_speed every frame, so the speed depends on the frame rate
     pub fn update_camera(&mut self,camera: &mut Camera){
+        self.move_camera(camera,self.translate_speed);
+    }
+
+    //Frame rate independent version of update_camera, translate_speed is how far it moves per frame at 60 fps
+    pub fn update_camera_with_time(&mut self,camera: &mut Camera,time: &Time){
+        self.move_camera(camera,self.translate_speed * time.delta_secs() * REFERENCE_FPS);
+    }
+
+    //Uses the app's input actions (move_forward/back/left/right, rotate, look_x) instead of the on_*_fn handlers
+    //See ActionMap::default for the default bindings
+    pub fn update_camera_with_input(&mut self,camera: &mut Camera,input: &Input,time: &Time){
+        self.front_pressed = input.is_action_held(actions::MOVE_FORWARD);
+        self.left_pressed = input.is_action_held(actions::MOVE_LEFT);
+        self.back_pressed = input.is_action_held(actions::MOVE_BACK);
+        self.right_pressed = input.is_action_held(actions::MOVE_RIGHT);
+        self.mouse_pressed = input.is_action_held(actions::ROTATE);
+        self.on_mouse_move_fn((input.axis(actions::LOOK_X),input.axis(actions::LOOK_Y)));
+        self.update_camera_with_time(camera,time);
+    }
+
+    fn move_camera(&mut self,camera: &mut Camera,distance: f32){
 
         if self.front_pressed {
-            camera.eye = camera.eye +  camera.forward * self.translate_speed;
+            camera.eye = camera.eye +  camera.forward * distance;
             camera.center = camera.eye + camera.forward;
         }
 
         if self.back_pressed{
-            camera.eye -= camera.forward * self.translate_speed;
+            camera.eye -= camera.forward * distance;
             camera.center = camera.eye + camera.forward;
         }
 
         if self.right_pressed{
-            camera.eye += camera.right * self.translate_speed;
+            camera.eye += camera.right * distance;
             camera.center = camera.eye + camera.forward;
 
         }
 
         if self.left_pressed{
-            camera.eye -= camera.right * self.translate_speed;
+            camera.eye -= camera.right * distance;
             camera.center = camera.eye + camera.forward;
         }
 
@@ -206,4 +245,25 @@ impl FlyCameraController{
         camera.right = nalgebra_glm::normalize(&camera.forward.cross(&camera.up));
 
     }
-}
\ No newline at end of file
+}
+
+
+//Drives a camera of the state with its FlyCameraController from ctx.input every frame (see update_camera_with_input)
+//select picks the camera and controller out of the state, ex: |state| (&mut state.camera,&mut state.camera_controller)
+pub struct FlyCameraPlugin<T>{
+    select: fn(&mut T) -> (&mut Camera,&mut FlyCameraController),
+}
+
+impl<T> FlyCameraPlugin<T>{
+    pub fn new(select: fn(&mut T) -> (&mut Camera,&mut FlyCameraController)) -> Self{
+        Self{ select }
+    }
+}
+
+//moves the camera before update, so update sees where it is this frame
+impl<T,E: 'static> Plugin<T,E> for FlyCameraPlugin<T>{
+    fn pre_update(&mut self,state: &mut T,ctx: &Context){
+        let (camera,controller) = (self.select)(state);
+        controller.update_camera_with_input(camera,&ctx.input,&ctx.time);
+    }
+}
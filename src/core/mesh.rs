@@ -1,6 +1,7 @@
 use tobj::LoadError;
 
 use crate::base::vertex::{VertexLayout};
+use crate::error::Result;
 
 pub struct Mesh<T: VertexLayout>{
     pub vertices: Vec<T>,
@@ -8,7 +9,7 @@ pub struct Mesh<T: VertexLayout>{
 }
 
 impl<T: VertexLayout> Mesh<T>{
-    pub fn from_obj(filename: &str) -> Result<Self,LoadError>{
+    pub fn from_obj(filename: &str) -> Result<Self>{
         let mut vertices = vec![];
         let mut indices: Vec<u16> = vec![];
 
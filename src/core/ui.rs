@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use egui::FontDefinitions;
 use egui_demo_lib::DemoWindows;
-use egui_wgpu_backend::RenderPass;
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
 use egui_winit_platform::{Platform, PlatformDescriptor};
 
 use crate::base::{app::App, context::Context};
@@ -13,6 +13,8 @@ pub struct Ui{
     pub platform: Platform,
     pub ui_render_pass: RenderPass,
     pub egui_demo_app: DemoWindows,
+    //physical pixels per egui point, follows the window's scale factor
+    scale_factor: f64,
 }
 
 impl Ui{
@@ -25,16 +27,45 @@ impl Ui{
             style: Default::default(),
         });
 
-        println!("ui scale factor: {}",scale_factor);
+        log::debug!("ui scale factor: {}",scale_factor);
     
         let ui_render_pass = RenderPass::new(&ctx.device,ctx.config.format,1);
             
         let egui_demo_app = egui_demo_lib::DemoWindows::default();
 
-        Self{
+        let mut ui = Self{
             platform,
             ui_render_pass,
             egui_demo_app,
+            scale_factor,
+        };
+        ui.set_scale_factor(ctx,scale_factor);
+        ui
+    }
+
+    pub fn scale_factor(&self) -> f64{
+        self.scale_factor
+    }
+
+    //Called by the app when the window's scale factor changes
+    //(the platform only rescales input, egui's pixels per point and screen size need to follow too)
+    pub fn set_scale_factor(&mut self,ctx: &Context,scale_factor: f64){
+        self.scale_factor = scale_factor;
+        let raw_input = self.platform.raw_input_mut();
+        let viewport_id = raw_input.viewport_id;
+        raw_input.viewports.entry(viewport_id).or_default().native_pixels_per_point = Some(scale_factor as f32);
+        raw_input.screen_rect = Some(egui::Rect::from_min_size(
+            Default::default(),
+            egui::vec2(ctx.size.width as f32,ctx.size.height as f32) / scale_factor as f32,
+        ));
+    }
+
+    //Screen descriptor matching the current target size and scale factor, for the ui render pass
+    pub fn screen_descriptor(&self,ctx: &Context) -> ScreenDescriptor{
+        ScreenDescriptor{
+            physical_width: ctx.config.width,
+            physical_height: ctx.config.height,
+            scale_factor: self.scale_factor as f32,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+//Everything that can go wrong inside hydra
+#[derive(Debug)]
+pub enum Error{
+    //adapter
+    NoAdapter,
+    AdapterNotFound(crate::base::adapter::AdapterSelector),
+    UnsupportedFeatures(wgpu::Features),
+    UnsupportedLimit{name: &'static str,requested: u64,allowed: u64},
+
+    //device
+    Device(wgpu::RequestDeviceError),
+
+    //surface
+    CreateSurface(wgpu::CreateSurfaceError),
+    Surface(wgpu::SurfaceError),
+    UnsupportedPresentMode{requested: wgpu::PresentMode,supported: Vec<wgpu::PresentMode>},
+    UnsupportedSurfaceFormat{requested: wgpu::TextureFormat,supported: Vec<wgpu::TextureFormat>},
+    //the context's adapter can't present to another window's surface
+    IncompatibleSurface(String),
+    //headless targets can't be empty
+    InvalidSize{width: u32,height: u32},
+
+    //shaders & pipelines
+    Shader(String),
+    UnsupportedSampleCount{format: wgpu::TextureFormat,sample_count: u32},
+    Pipeline(String),
+
+    //reading textures back to the cpu
+    Capture(String),
+    SaveImage(image::ImageError),
+
+    //assets
+    Texture(image::ImageError),
+    Mesh(tobj::LoadError),
+
+    //windowing
+    EventLoop(winit::error::EventLoopError),
+    Window(winit::error::OsError),
+    Icon(winit::window::BadIcon),
+    CursorGrab(winit::error::ExternalError),
+
+    //config files
+    Actions(String),
+    Replay(String),
+
+    //app/settings combinations that can't work
+    InvalidConfig(String),
+}
+
+pub type Result<T> = std::result::Result<T,Error>;
+
+impl std::fmt::Display for Error{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            Error::NoAdapter => write!(f,"no adapter matches the requested backends/power preference"),
+            Error::AdapterNotFound(selector) => write!(f,"no adapter matches {selector:?}"),
+            Error::UnsupportedFeatures(features) => write!(f,"adapter doesn't support required features: {features:?}"),
+            Error::UnsupportedLimit { name, requested, allowed } => write!(f,"adapter limit {name} is {allowed}, but {requested} was requested"),
+            Error::Device(e) => write!(f,"failed to request device: {e}"),
+            Error::CreateSurface(e) => write!(f,"failed to create surface: {e}"),
+            Error::Surface(e) => write!(f,"failed to acquire surface texture: {e}"),
+            Error::UnsupportedPresentMode { requested, supported } => write!(f,"present mode {requested:?} isn't supported by the surface (supported: {supported:?})"),
+            Error::UnsupportedSurfaceFormat { requested, supported } => write!(f,"surface format {requested:?} isn't supported by the surface (supported: {supported:?})"),
+            Error::IncompatibleSurface(label) => write!(f,"the adapter can't present to the surface of window {label}"),
+            Error::InvalidSize { width, height } => write!(f,"invalid target size {width}x{height}, width and height have to be at least 1"),
+            Error::Shader(e) => write!(f,"shader compilation failed: {e}"),
+            Error::UnsupportedSampleCount { format, sample_count } => write!(f,"adapter doesn't support {sample_count}x multisampling for {format:?}"),
+            Error::Pipeline(e) => write!(f,"pipeline creation failed: {e}"),
+            Error::Capture(e) => write!(f,"failed to capture texture: {e}"),
+            Error::SaveImage(e) => write!(f,"failed to save image: {e}"),
+            Error::Texture(e) => write!(f,"failed to load texture: {e}"),
+            Error::Mesh(e) => write!(f,"failed to load mesh: {e}"),
+            Error::EventLoop(e) => write!(f,"event loop error: {e}"),
+            Error::Window(e) => write!(f,"failed to create window: {e}"),
+            Error::Icon(e) => write!(f,"invalid window icon: {e}"),
+            Error::CursorGrab(e) => write!(f,"failed to grab cursor: {e}"),
+            Error::Actions(e) => write!(f,"input bindings error: {e}"),
+            Error::Replay(e) => write!(f,"input recording error: {e}"),
+            Error::InvalidConfig(e) => write!(f,"invalid configuration: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self{
+            Error::Device(e) => Some(e),
+            Error::CreateSurface(e) => Some(e),
+            Error::Surface(e) => Some(e),
+            Error::SaveImage(e) => Some(e),
+            Error::Texture(e) => Some(e),
+            Error::Mesh(e) => Some(e),
+            Error::EventLoop(e) => Some(e),
+            Error::Window(e) => Some(e),
+            Error::Icon(e) => Some(e),
+            Error::CursorGrab(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error{
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        Error::Device(e)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for Error{
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        Error::CreateSurface(e)
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error{
+    fn from(e: wgpu::SurfaceError) -> Self {
+        Error::Surface(e)
+    }
+}
+
+impl From<image::ImageError> for Error{
+    fn from(e: image::ImageError) -> Self {
+        Error::Texture(e)
+    }
+}
+
+impl From<tobj::LoadError> for Error{
+    fn from(e: tobj::LoadError) -> Self {
+        Error::Mesh(e)
+    }
+}
+
+impl From<winit::error::EventLoopError> for Error{
+    fn from(e: winit::error::EventLoopError) -> Self {
+        Error::EventLoop(e)
+    }
+}
+
+impl From<winit::error::OsError> for Error{
+    fn from(e: winit::error::OsError) -> Self {
+        Error::Window(e)
+    }
+}
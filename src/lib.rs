@@ -1,2 +1,5 @@
 pub mod base;
-pub mod core;
\ No newline at end of file
+pub mod core;
+pub mod error;
+
+pub use error::{Error, Result};